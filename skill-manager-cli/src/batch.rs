@@ -0,0 +1,253 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+use structopt::StructOpt;
+
+/// Runs the commands of a script in a single load and persist cycle.
+///
+/// Each line holds one command with the same syntax as on the command line. Empty lines and
+/// lines starting with `#` are skipped. `$<n>.<field>` is replaced with a field of the result
+/// of line `n`, e.g. `$1.id` or `$2.items.0.label` for the results of `find`, and `$$` with a
+/// literal `$`.
+#[derive(StructOpt)]
+pub struct BatchArgs {
+    /// The script to run, `-` reads it from stdin
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+    /// Stops at the first failing command instead of continuing with the next one
+    #[structopt(long = "stop-on-error")]
+    stop_on_error: bool,
+    /// Stops at the first failing command and saves no changes at all
+    #[structopt(long = "all-or-nothing")]
+    pub all_or_nothing: bool,
+}
+
+/// Runs every command of the script with `execute`, which returns the command's result.
+///
+/// Failures are reported on stderr with their line number. Returns the number of failed
+/// commands.
+pub fn run(
+    args: &BatchArgs,
+    mut execute: impl FnMut(Vec<String>) -> Result<Option<Value>>,
+) -> Result<usize> {
+    let script = read_script(args)?;
+    let mut results = HashMap::new();
+    let mut failed = 0;
+    for (index, line) in script.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let outcome = split_words(line)
+            .and_then(|words| {
+                words
+                    .iter()
+                    .map(|word| substitute(word, &results))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .and_then(&mut execute);
+        match outcome {
+            Ok(Some(result)) => {
+                results.insert(line_number, result);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("Error in line {}: {}", line_number, e);
+                failed += 1;
+                if args.stop_on_error || args.all_or_nothing {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(failed)
+}
+
+fn read_script(args: &BatchArgs) -> Result<String> {
+    if args.file.as_os_str() == "-" {
+        let mut script = String::new();
+        io::stdin().read_to_string(&mut script)?;
+        Ok(script)
+    } else {
+        fs::read_to_string(&args.file)
+            .with_context(|| format!("Failed to read script {}", args.file.display()))
+    }
+}
+
+/// Splits a line into words like a shell, honoring single quotes, double quotes and
+/// backslash escapes.
+fn split_words(line: &str) -> Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ '"') | Some(c @ '\\') => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(anyhow!("Unterminated double quote")),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow!("Unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err(anyhow!("Backslash at the end of the line")),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Replaces the references to results of earlier lines in a word.
+fn substitute(word: &str, results: &HashMap<usize, Value>) -> Result<String> {
+    let mut substituted = String::new();
+    let mut rest = word;
+    while let Some(start) = rest.find('$') {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if rest.starts_with('$') {
+            substituted.push('$');
+            rest = &rest[1..];
+            continue;
+        }
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len());
+        let reference = rest[..end].trim_end_matches('.');
+        rest = &rest[reference.len()..];
+        substituted.push_str(&resolve_reference(reference, results)?);
+    }
+    substituted.push_str(rest);
+    Ok(substituted)
+}
+
+fn resolve_reference(reference: &str, results: &HashMap<usize, Value>) -> Result<String> {
+    let mut path = reference.split('.');
+    let line_number: usize = path
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| anyhow!("Invalid reference ${}, expected $<line>.<field>", reference))?;
+    let mut value = results
+        .get(&line_number)
+        .ok_or_else(|| anyhow!("Line {} has no result to reference", line_number))?;
+    for field in path {
+        value = match value {
+            Value::Array(items) => field.parse::<usize>().ok().and_then(|i| items.get(i)),
+            Value::Object(fields) => fields.get(field),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("The result of ${} has no field {}", reference, field))?;
+    }
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    #[test]
+    fn split_words_test() -> Result<()> {
+        assert_eq!(
+            split_words(r#"employee add -f 'Ada Augusta' -l "Love\"lace" --title Dr.\ Phil"#)?,
+            vec![
+                "employee",
+                "add",
+                "-f",
+                "Ada Augusta",
+                "-l",
+                "Love\"lace",
+                "--title",
+                "Dr. Phil"
+            ]
+        );
+        assert_eq!(split_words("  skill   find ''")?, vec!["skill", "find", ""]);
+        assert!(split_words("skill add 'Rust").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn substitute_test() -> Result<()> {
+        let mut results = HashMap::new();
+        results.insert(1, json!({"id": "abc", "version": 2}));
+        results.insert(3, json!([{"label": "Rust"}]));
+
+        assert_eq!(substitute("$1.id", &results)?, "abc");
+        assert_eq!(substitute("v$1.version.", &results)?, "v2.");
+        assert_eq!(substitute("$3.0.label/$$", &results)?, "Rust/$");
+        assert!(substitute("$2.id", &results).is_err());
+        assert!(substitute("$1.name", &results).is_err());
+        assert!(substitute("$x", &results).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn run_test() -> Result<()> {
+        let mut script = tempfile::NamedTempFile::new()?;
+        writeln!(
+            script,
+            "# create a skill\nskill add Rust\n\nfail\nskill get $2.id"
+        )?;
+        let args = |stop_on_error| BatchArgs {
+            file: script.path().into(),
+            stop_on_error,
+            all_or_nothing: false,
+        };
+        let run_script = |stop_on_error| -> Result<(usize, Vec<String>)> {
+            let mut executed = vec![];
+            let failed = run(&args(stop_on_error), |words| {
+                executed.push(words.join(" "));
+                match words[0].as_str() {
+                    "fail" => Err(anyhow!("failed")),
+                    _ => Ok(Some(json!({"id": "abc"}))),
+                }
+            })?;
+            Ok((failed, executed))
+        };
+
+        let (failed, executed) = run_script(false)?;
+        assert_eq!(failed, 1);
+        assert_eq!(executed, vec!["skill add Rust", "fail", "skill get abc"]);
+
+        let (failed, executed) = run_script(true)?;
+        assert_eq!(failed, 1);
+        assert_eq!(executed, vec!["skill add Rust", "fail"]);
+        Ok(())
+    }
+}
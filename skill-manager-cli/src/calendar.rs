@@ -0,0 +1,239 @@
+use crate::{
+    resolve,
+    vcard::{escape, fold},
+};
+use anyhow::{anyhow, Result};
+use skill_manager::{
+    employees::{usecase::FindEmployees, Employee, ProjectAssignment},
+    projects::usecase::{FindProjects, GetProject},
+};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use time::{Date, UtcOffset};
+
+/// Exports project assignments as all-day events of an iCalendar (.ics) file, all
+/// assignments unless restricted to an employee or a project.
+///
+/// Assignments without an end date are shown until today.
+#[derive(StructOpt)]
+pub struct CalendarArgs {
+    /// Only the assignments of this employee
+    #[structopt(short = "e", long = "employee")]
+    employee: Option<String>,
+    /// Only the assignments to this project
+    #[structopt(short = "p", long = "project")]
+    project: Option<String>,
+    /// The .ics file to write, stdout if omitted
+    #[structopt(short = "o", long = "file", parse(from_os_str))]
+    pub file: Option<PathBuf>,
+}
+
+pub fn export(
+    args: &CalendarArgs,
+    employees: &impl FindEmployees,
+    projects: &(impl FindProjects + GetProject),
+    today: Date,
+) -> Result<String> {
+    let employee_id = args
+        .employee
+        .as_ref()
+        .map(|reference| resolve::employee(employees, reference))
+        .transpose()?;
+    let project_label = match &args.project {
+        Some(reference) => {
+            let id = resolve::project(projects, reference)?;
+            let project = projects
+                .get(id.clone())?
+                .ok_or_else(|| anyhow!("Project {} not found", id))?;
+            Some(project.label)
+        }
+        None => None,
+    };
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//skill-manager//skill-manager-cli//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for employee in employees.find_employees(&Default::default())?.items {
+        if employee_id.is_some() && employee_id.as_ref() != Some(&employee.id) {
+            continue;
+        }
+        let mut assignments: Vec<_> = employee
+            .projects
+            .iter()
+            .filter(|assignment| match &project_label {
+                Some(label) => *label == assignment.label,
+                None => true,
+            })
+            .collect();
+        assignments.sort_by_key(|assignment| assignment.start_date);
+        for assignment in assignments {
+            lines.extend(event(&employee, assignment, today));
+        }
+    }
+    lines.push("END:VCALENDAR".to_string());
+    Ok(lines.iter().map(|line| fold(line)).collect())
+}
+
+/// An all-day event, whose end date is exclusive.
+fn event(employee: &Employee, assignment: &ProjectAssignment, today: Date) -> Vec<String> {
+    let end_date = assignment
+        .end_date
+        .unwrap_or(today)
+        .max(assignment.start_date);
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@skill-manager", assignment.id),
+        format!(
+            "DTSTAMP:{}",
+            employee
+                .last_update
+                .to_offset(UtcOffset::UTC)
+                .format("%Y%m%dT%H%M%SZ")
+        ),
+        format!(
+            "DTSTART;VALUE=DATE:{}",
+            assignment.start_date.format("%Y%m%d")
+        ),
+        format!("DTEND;VALUE=DATE:{}", end_date.next_day().format("%Y%m%d")),
+        format!(
+            "SUMMARY:{}",
+            escape(&format!(
+                "{} {}: {}",
+                employee.first_name, employee.last_name, assignment.label
+            ))
+        ),
+    ];
+    if !assignment.contribution.0.is_empty() {
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape(&assignment.contribution.0)
+        ));
+    }
+    lines.push("TRANSP:TRANSPARENT".to_string());
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{
+            usecase::{
+                AddEmployee, AddEmployeeRequest, CreateProjectAssignment, ProjectAssignmentRequest,
+            },
+            EmailAddress, FirstName, LastName, ProjectContribution, TelephoneNumber, Title,
+        },
+        projects::{usecase::AddProject, ProjectDescription, ProjectLabel},
+    };
+    use skill_manager_file::unit_of_work::UnitOfWork;
+    use time::date;
+
+    fn setup() -> Result<UnitOfWork> {
+        let mut uow = UnitOfWork::in_memory();
+        let project = uow.projects.add(
+            ProjectLabel("Skill Manager".into()),
+            ProjectDescription("".into()),
+        )?;
+        let other = uow
+            .projects
+            .add(ProjectLabel("Other".into()), ProjectDescription("".into()))?;
+        for (first_name, last_name) in &[("Ada", "Lovelace"), ("Alan", "Turing")] {
+            let employee = uow.employees.add(AddEmployeeRequest {
+                first_name: FirstName(first_name.to_string()),
+                last_name: LastName(last_name.to_string()),
+                title: Title("".into()),
+                email: EmailAddress(format!("{}@example.com", first_name)),
+                telephone: TelephoneNumber("".into()),
+            })?;
+            for (project_id, end_date) in &[
+                (project.id.clone(), Some(date!(2020 - 03 - 31))),
+                (other.id.clone(), None),
+            ] {
+                uow.employees
+                    .with(&*uow.projects)
+                    .create_project_assignment(ProjectAssignmentRequest {
+                        employee_id: employee.id.clone(),
+                        project_id: project_id.clone(),
+                        contribution: ProjectContribution("Development, Review".into()),
+                        start_date: date!(2020 - 01 - 01),
+                        end_date: *end_date,
+                        expected_version: None,
+                    })?;
+            }
+        }
+        Ok(uow)
+    }
+
+    fn calendar(uow: &UnitOfWork, employee: Option<&str>, project: Option<&str>) -> Result<String> {
+        let args = CalendarArgs {
+            employee: employee.map(Into::into),
+            project: project.map(Into::into),
+            file: None,
+        };
+        export(
+            &args,
+            &*uow.employees,
+            &*uow.projects,
+            date!(2020 - 06 - 30),
+        )
+    }
+
+    fn summaries(ics: &str) -> Vec<&str> {
+        ics.split("\r\n")
+            .filter_map(|line| line.strip_prefix("SUMMARY:"))
+            .collect()
+    }
+
+    #[test]
+    fn filter_assignments_test() -> Result<()> {
+        let uow = setup()?;
+
+        assert_eq!(summaries(&calendar(&uow, None, None)?).len(), 4);
+        assert_eq!(
+            summaries(&calendar(&uow, Some("alan@example.com"), None)?),
+            vec!["Alan Turing: Skill Manager", "Alan Turing: Other"]
+        );
+        assert_eq!(
+            summaries(&calendar(&uow, None, Some("other"))?),
+            vec!["Ada Lovelace: Other", "Alan Turing: Other"]
+        );
+        assert_eq!(
+            summaries(&calendar(
+                &uow,
+                Some("ada@example.com"),
+                Some("skill manager")
+            )?),
+            vec!["Ada Lovelace: Skill Manager"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn all_day_events_test() -> Result<()> {
+        let uow = setup()?;
+
+        let ics = calendar(&uow, Some("ada@example.com"), None)?;
+        let lines: Vec<_> = ics.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line.starts_with("DT") && !line.starts_with("DTSTAMP"))
+                .collect::<Vec<_>>(),
+            vec![
+                &"DTSTART;VALUE=DATE:20200101",
+                &"DTEND;VALUE=DATE:20200401",
+                &"DTSTART;VALUE=DATE:20200101",
+                &"DTEND;VALUE=DATE:20200701"
+            ]
+        );
+        assert!(lines.contains(&"DESCRIPTION:Development\\, Review"));
+        assert_eq!(lines[lines.len() - 2], "END:VCALENDAR");
+        Ok(())
+    }
+}
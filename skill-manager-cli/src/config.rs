@@ -0,0 +1,150 @@
+use crate::{output::OutputFormat, profile::ProfileFormat};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use skill_manager_file::{
+    backend::{Backend, StorageConfig},
+    unit_of_work::UnitOfWork,
+};
+use std::{path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+const TEMPLATES_DIR: &str = "templates";
+
+#[derive(StructOpt)]
+pub struct ConfigArgs {
+    #[structopt(long = "config", global = true, parse(from_os_str))]
+    config: Option<PathBuf>,
+    #[structopt(
+        long = "data-dir",
+        env = "SKILL_MANAGER_DATA_DIR",
+        global = true,
+        parse(from_os_str)
+    )]
+    data_dir: Option<PathBuf>,
+    #[structopt(long = "backend", global = true)]
+    backend: Option<Backend>,
+    /// table, json, jsonl, csv or yaml, or markdown, html or latex for `employee profile`
+    #[structopt(long = "format", global = true)]
+    format: Option<FormatArg>,
+}
+
+/// The value of `--format`: the format of records or the document format of a profile.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum FormatArg {
+    Output(OutputFormat),
+    Profile(ProfileFormat),
+}
+
+impl FromStr for FormatArg {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(format) = s.parse() {
+            return Ok(FormatArg::Output(format));
+        }
+        s.parse().map(FormatArg::Profile).map_err(|_| {
+            anyhow!(
+                "Unknown format {}, expected table, json, jsonl, csv or yaml, or markdown, html \
+                 or latex for `employee profile`",
+                s
+            )
+        })
+    }
+}
+
+/// The settings of the CLI's config file besides the storage settings, every setting is
+/// optional.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    format: Option<OutputFormat>,
+}
+
+/// The settings of the CLI after combining flags, environment variables, config file and
+/// defaults, in that order of precedence.
+#[derive(Debug, PartialEq)]
+pub struct Config {
+    pub storage: StorageConfig,
+    pub format: OutputFormat,
+    /// The document format given with `--format`, only `employee profile` accepts one.
+    pub profile_format: Option<ProfileFormat>,
+    /// The directory next to the config file that holds user-defined templates.
+    pub templates_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn resolve(args: ConfigArgs) -> Result<Self> {
+        let (storage, settings) = StorageConfig::resolve(args.config, args.data_dir, args.backend)?;
+        let templates_dir = storage
+            .config_path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.join(TEMPLATES_DIR));
+        let (format, profile_format) = match args.format {
+            Some(FormatArg::Output(format)) => (Some(format), None),
+            Some(FormatArg::Profile(format)) => (None, Some(format)),
+            None => (None, None),
+        };
+        Ok(Self {
+            storage,
+            format: merge_format(format, settings),
+            profile_format,
+            templates_dir,
+        })
+    }
+
+    pub fn open_unit_of_work(&self) -> Result<UnitOfWork> {
+        self.storage.open()
+    }
+}
+
+fn merge_format(format: Option<OutputFormat>, settings: Settings) -> OutputFormat {
+    format.or(settings.format).unwrap_or(OutputFormat::Json)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_settings_test() -> Result<()> {
+        let settings: Settings = toml::from_str("format = \"table\"")?;
+        assert_eq!(
+            settings,
+            Settings {
+                format: Some(OutputFormat::Table),
+            }
+        );
+        assert!(toml::from_str::<Settings>("output = \"table\"").is_err());
+        assert!(toml::from_str::<Settings>("format = \"html\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_format_arg_test() -> Result<()> {
+        assert_eq!(
+            "csv".parse::<FormatArg>()?,
+            FormatArg::Output(OutputFormat::Csv)
+        );
+        assert_eq!(
+            "latex".parse::<FormatArg>()?,
+            FormatArg::Profile(ProfileFormat::Latex)
+        );
+        let error = "pdf".parse::<FormatArg>().unwrap_err();
+        assert!(error.to_string().contains("markdown, html or latex"));
+        Ok(())
+    }
+
+    #[test]
+    fn format_precedence_test() {
+        assert_eq!(merge_format(None, Settings::default()), OutputFormat::Json);
+        let settings = || Settings {
+            format: Some(OutputFormat::Csv),
+        };
+        assert_eq!(merge_format(None, settings()), OutputFormat::Csv);
+        assert_eq!(
+            merge_format(Some(OutputFormat::Table), settings()),
+            OutputFormat::Table
+        );
+    }
+}
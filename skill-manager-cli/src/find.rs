@@ -0,0 +1,270 @@
+use anyhow::{anyhow, Result};
+use skill_manager::{
+    employees::{
+        usecase::{EmployeeQuery, EmployeeSortKey, FindEmployees},
+        Employee,
+    },
+    projects::{
+        usecase::{FindProjects, ProjectSortKey},
+        Project,
+    },
+    query::{Page, PageRequest, Query, SortDirection},
+    skills::{
+        usecase::{FindSkills, SkillSortKey},
+        Skill,
+    },
+};
+use std::{fmt, str::FromStr};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+pub struct FindArgs {
+    /// Only list entries containing this text, ignoring case
+    #[structopt(long = "filter")]
+    filter: Option<String>,
+    /// One of name, last-update or start-date
+    #[structopt(long = "sort", default_value = "name")]
+    sort: SortKey,
+    #[structopt(long = "desc")]
+    descending: bool,
+    #[structopt(long = "limit")]
+    limit: Option<usize>,
+    #[structopt(long = "offset", default_value = "0")]
+    offset: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SortKey {
+    Name,
+    LastUpdate,
+    StartDate,
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "name" => Ok(SortKey::Name),
+            "last-update" => Ok(SortKey::LastUpdate),
+            "start-date" => Ok(SortKey::StartDate),
+            _ => Err(anyhow!(
+                "Unknown sort key {}, expected name, last-update or start-date",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SortKey::Name => "name",
+            SortKey::LastUpdate => "last-update",
+            SortKey::StartDate => "start-date",
+        })
+    }
+}
+
+impl FindArgs {
+    fn query<S>(&self, sort: S) -> Query<S> {
+        Query {
+            filter: self.filter.clone(),
+            sort,
+            direction: self.direction(),
+            page: self.page(),
+        }
+    }
+
+    fn direction(&self) -> SortDirection {
+        if self.descending {
+            SortDirection::Descending
+        } else {
+            SortDirection::Ascending
+        }
+    }
+
+    fn page(&self) -> PageRequest {
+        PageRequest {
+            offset: self.offset,
+            limit: self.limit,
+        }
+    }
+}
+
+pub fn skills(skills: &impl FindSkills, args: &FindArgs) -> Result<Page<Skill>> {
+    let sort = match args.sort {
+        SortKey::Name => SkillSortKey::Label,
+        key => return Err(unsupported_sort_key("skills", key)),
+    };
+    Ok(skills.find_skills(&args.query(sort))?)
+}
+
+/// The start date of a project is the earliest start date of any assignment to it, projects
+/// without assignments come last. Since only employees know about it, sorting by start date
+/// happens here instead of in the backend.
+pub fn projects(
+    projects: &impl FindProjects,
+    employees: &impl FindEmployees,
+    args: &FindArgs,
+) -> Result<Page<Project>> {
+    match args.sort {
+        SortKey::Name => Ok(projects.find_projects(&args.query(ProjectSortKey::Label))?),
+        SortKey::StartDate => {
+            let employees = employees.find_employees(&EmployeeQuery::default())?.items;
+            let mut found = projects
+                .find_projects(&Query {
+                    page: PageRequest::default(),
+                    ..args.query(ProjectSortKey::Label)
+                })?
+                .items;
+            let start_date = |project: &Project| {
+                employees
+                    .iter()
+                    .flat_map(|employee| &employee.projects)
+                    .filter(|assignment| assignment.label == project.label)
+                    .map(|assignment| assignment.start_date)
+                    .min()
+            };
+            found.sort_by(|a, b| {
+                args.direction()
+                    .apply_missing_last(&start_date(a), &start_date(b))
+                    .then_with(|| a.cmp(b))
+            });
+            Ok(Page::paginate(found, args.page()))
+        }
+        key => Err(unsupported_sort_key("projects", key)),
+    }
+}
+
+pub fn employees(employees: &impl FindEmployees, args: &FindArgs) -> Result<Page<Employee>> {
+    let sort = match args.sort {
+        SortKey::Name => EmployeeSortKey::Name,
+        SortKey::LastUpdate => EmployeeSortKey::LastUpdate,
+        SortKey::StartDate => EmployeeSortKey::StartDate,
+    };
+    Ok(employees.find_employees(&args.query(sort))?)
+}
+
+fn unsupported_sort_key(kind: &str, key: SortKey) -> anyhow::Error {
+    anyhow!("Cannot sort {} by {}", kind, key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{
+            EmailAddress, EmployeeId, FirstName, LastName, ProjectAssignment, ProjectAssignmentId,
+            ProjectContribution, TelephoneNumber, Title,
+        },
+        projects::{usecase::ProjectQuery, ProjectDescription, ProjectId, ProjectLabel},
+        skills::{usecase::SkillQuery, SkillId, SkillLabel},
+        version::Version,
+    };
+    use time::{date, Date, OffsetDateTime};
+    use uuid::Uuid;
+
+    fn args(sort: SortKey, descending: bool) -> FindArgs {
+        FindArgs {
+            filter: None,
+            sort,
+            descending,
+            limit: None,
+            offset: 0,
+        }
+    }
+
+    fn project(label: &str) -> Project {
+        Project {
+            id: ProjectId(Uuid::new_v4()),
+            label: ProjectLabel(label.into()),
+            description: ProjectDescription("".into()),
+            version: Version::default(),
+        }
+    }
+
+    fn assignment(project: &Project, start_date: Date) -> ProjectAssignment {
+        ProjectAssignment {
+            id: ProjectAssignmentId(Uuid::new_v4()),
+            label: project.label.clone(),
+            description: project.description.clone(),
+            contribution: ProjectContribution("".into()),
+            start_date,
+            end_date: None,
+        }
+    }
+
+    #[test]
+    fn sort_projects_by_start_date_test() -> Result<()> {
+        let all_projects = vec![project("Old"), project("Unstaffed"), project("New")];
+        let employee = Employee {
+            id: EmployeeId(Uuid::new_v4()),
+            first_name: FirstName("Ada".into()),
+            last_name: LastName("Lovelace".into()),
+            title: Title("".into()),
+            email: EmailAddress("".into()),
+            telephone: TelephoneNumber("".into()),
+            skills: vec![],
+            projects: vec![
+                assignment(&all_projects[2], date!(2020 - 03 - 01)),
+                assignment(&all_projects[0], date!(2019 - 01 - 01)),
+                assignment(&all_projects[2], date!(2018 - 06 - 01)),
+            ],
+            last_update: OffsetDateTime::now_utc(),
+            version: Version::default(),
+        };
+        let find_projects =
+            |query: &ProjectQuery| Ok(Page::paginate(all_projects.clone(), query.page));
+        let find_employees =
+            |query: &EmployeeQuery| Ok(Page::paginate(vec![employee.clone()], query.page));
+        let labels = |page: Page<Project>| -> Vec<String> {
+            page.items.into_iter().map(|p| p.label.0).collect()
+        };
+
+        let found = projects(
+            &find_projects,
+            &find_employees,
+            &args(SortKey::StartDate, false),
+        )?;
+        assert_eq!(labels(found), vec!["New", "Old", "Unstaffed"]);
+
+        let found = projects(
+            &find_projects,
+            &find_employees,
+            &args(SortKey::StartDate, true),
+        )?;
+        assert_eq!(labels(found), vec!["Old", "New", "Unstaffed"]);
+
+        let mut find_args = args(SortKey::StartDate, true);
+        find_args.limit = Some(1);
+        let found = projects(&find_projects, &find_employees, &find_args)?;
+        assert_eq!(found.total, 3);
+        assert_eq!(labels(found), vec!["Old"]);
+
+        assert!(projects(
+            &find_projects,
+            &find_employees,
+            &args(SortKey::LastUpdate, false)
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_sort_key_test() {
+        let find_skills = |_: &SkillQuery| -> skill_manager::Result<Page<Skill>> {
+            Ok(Page::paginate(
+                vec![Skill {
+                    id: SkillId(Uuid::new_v4()),
+                    label: SkillLabel("Rust".into()),
+                    version: Version::default(),
+                }],
+                PageRequest::default(),
+            ))
+        };
+
+        assert!(skills(&find_skills, &args(SortKey::Name, false)).is_ok());
+        assert!(skills(&find_skills, &args(SortKey::StartDate, false)).is_err());
+    }
+}
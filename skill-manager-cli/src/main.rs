@@ -1,6 +1,11 @@
-use anyhow::Result;
-use employees::EmployeeDb;
-use serde::{de::DeserializeOwned, Serialize};
+use anyhow::{anyhow, Context, Result};
+use batch::BatchArgs;
+use calendar::CalendarArgs;
+use config::{Config, ConfigArgs};
+use find::FindArgs;
+use output::{Deleted, Output};
+use profile::{ProfileArgs, ProfileFormat};
+use resume::{ExportResumeArgs, ImportResumeArgs};
 use skill_manager::{
     employees::{
         usecase::{
@@ -8,36 +13,84 @@ use skill_manager::{
             GetEmployeeById, ProjectAssignmentRequest, SetSkillKnowledgeOfEmployee,
             SetSkillKnowledgeRequest,
         },
-        EmailAddress, EmployeeId, FirstName, LastName, ProjectContribution, SkillLevel,
-        TelephoneNumber, Title,
+        EmailAddress, FirstName, LastName, ProjectContribution, SkillLevel, TelephoneNumber, Title,
     },
     projects::{
         usecase::{AddProject, DeleteProject, GetProject},
-        ProjectDescription, ProjectId, ProjectLabel,
+        ProjectDescription, ProjectLabel,
     },
     skills::{
-        usecase::{AddSkill, DeleteSkillById, FindSkills, GetSkillById},
-        SkillId, SkillLabel,
+        usecase::{AddSkill, DeleteSkillById, GetSkillById},
+        SkillLabel,
     },
+    version::Version,
 };
-use skill_manager_in_memory::{employees, projects::ProjectDb, skills::SkillDb};
-use std::{fs, fs::File, io, path::Path, process};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use std::{fs, process};
 use structopt::StructOpt;
-use time::Date;
+use time::{Date, OffsetDateTime};
+use transfer::{ExportArgs, ImportArgs};
+use vcard::{ExportVcardArgs, ImportVcardArgs};
+
+mod batch;
+mod calendar;
+mod config;
+mod find;
+mod output;
+mod profile;
+mod resolve;
+mod resume;
+mod transfer;
+mod vcard;
+
+#[derive(StructOpt)]
+struct Opt {
+    #[structopt(flatten)]
+    config: ConfigArgs,
+    #[structopt(subcommand)]
+    command: Command,
+}
 
 #[derive(StructOpt)]
-enum Opt {
+enum Command {
     Skill(SkillCommand),
     Project(ProjectCommand),
     Employee(EmployeeCommand),
+    Migrate {
+        #[structopt(long = "dry-run")]
+        dry_run: bool,
+    },
+    Batch(BatchArgs),
+    Import(ImportArgs),
+    Export(ExportArgs),
+    Calendar(CalendarArgs),
+}
+
+/// A line of a batch script.
+#[derive(StructOpt)]
+#[structopt(name = "batch")]
+struct BatchLine {
+    #[structopt(subcommand)]
+    command: Command,
 }
 
 #[derive(StructOpt)]
 enum SkillCommand {
-    Add { label: SkillLabel },
-    Find {},
-    Get { id: SkillId },
-    Delete { id: SkillId },
+    Add {
+        label: SkillLabel,
+    },
+    Find {
+        #[structopt(flatten)]
+        args: FindArgs,
+    },
+    Get {
+        skill: String,
+    },
+    Delete {
+        skill: String,
+        #[structopt(long = "expected-version")]
+        expected_version: Option<Version>,
+    },
 }
 
 #[derive(StructOpt)]
@@ -49,10 +102,16 @@ enum ProjectCommand {
         description: ProjectDescription,
     },
     Delete {
-        id: ProjectId,
+        project: String,
+        #[structopt(long = "expected-version")]
+        expected_version: Option<Version>,
     },
     Get {
-        id: ProjectId,
+        project: String,
+    },
+    Find {
+        #[structopt(flatten)]
+        args: FindArgs,
     },
 }
 
@@ -71,132 +130,205 @@ enum EmployeeCommand {
         telephone: Option<TelephoneNumber>,
     },
     Delete {
-        id: EmployeeId,
+        employee: String,
+        #[structopt(long = "expected-version")]
+        expected_version: Option<Version>,
     },
     Get {
-        id: EmployeeId,
+        employee: String,
+    },
+    Profile(ProfileArgs),
+    ExportResume(ExportResumeArgs),
+    ImportResume(ImportResumeArgs),
+    ExportVcard(ExportVcardArgs),
+    ImportVcard(ImportVcardArgs),
+    Find {
+        #[structopt(flatten)]
+        args: FindArgs,
     },
     AssignProject {
-        #[structopt(short = "e", long = "employee-id")]
-        employee_id: EmployeeId,
-        #[structopt(short = "p", long = "project-id")]
-        project_id: ProjectId,
+        #[structopt(short = "e", long = "employee")]
+        employee: String,
+        #[structopt(short = "p", long = "project")]
+        project: String,
         #[structopt(short = "d", long = "start-date", parse(try_from_str = parse_date))]
         start_date: Date,
         #[structopt(long = "end-date", parse(try_from_str = parse_date))]
         end_date: Option<Date>,
+        #[structopt(long = "expected-version")]
+        expected_version: Option<Version>,
         contribution: ProjectContribution,
     },
     AssignSkill {
-        #[structopt(short = "e", long = "employee-id")]
-        employee_id: EmployeeId,
-        #[structopt(short = "s", long = "skill-id")]
-        skill_id: SkillId,
+        #[structopt(short = "e", long = "employee")]
+        employee: String,
+        #[structopt(short = "s", long = "skill")]
+        skill: String,
         #[structopt(short = "l", long = "skill-level")]
         skill_level: SkillLevel,
         #[structopt(long = "secret")]
         secret: bool,
+        #[structopt(long = "expected-version")]
+        expected_version: Option<Version>,
     },
 }
 
-struct FileBackedDb<T> {
-    db: T,
-    file_path: Box<dyn AsRef<Path>>,
-}
-
-impl<T: Serialize + DeserializeOwned + Default> FileBackedDb<T> {
-    fn from_path(file_path: Box<dyn AsRef<Path>>) -> Result<Self> {
-        let val = match fs::read_to_string(&*file_path) {
-            Ok(file_contents) => serde_json::from_str(&file_contents)?,
-            _ => Default::default(),
-        };
-        Ok(FileBackedDb { db: val, file_path })
-    }
-    fn persist(self) -> Result<()> {
-        let file = File::create(&*self.file_path)?;
-        serde_json::to_writer_pretty(io::BufWriter::new(file), &self.db)?;
-        Ok(())
-    }
-}
-
 fn parse_date(s: &str) -> Result<Date> {
     time::parse(s, "%F").map_err(Into::into)
 }
 
 fn main() {
-    let skill_db: FileBackedDb<SkillDb> =
-        FileBackedDb::from_path(Box::new("./skills.json")).unwrap();
-    let project_db: FileBackedDb<ProjectDb> =
-        FileBackedDb::from_path(Box::new("./projects.json")).unwrap();
-    let employee_db: FileBackedDb<EmployeeDb> =
-        FileBackedDb::from_path(Box::new("./employees.json")).unwrap();
-
-    let command = Opt::from_args();
+    let opt = Opt::from_args();
 
-    if let Err(e) = match command {
-        Opt::Skill(skill_command) => skill_op(skill_command, skill_db),
-        Opt::Project(project_command) => project_op(project_command, project_db),
-        Opt::Employee(employee_command) => {
-            employee_op(employee_command, employee_db, project_db, skill_db)
-        }
-    } {
+    if let Err(e) = run(opt) {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
 }
 
-fn print_json(val: &impl Serialize) {
-    println!("{}", serde_json::to_string_pretty(val).unwrap());
+fn run(opt: Opt) -> Result<()> {
+    let config = Config::resolve(opt.config)?;
+    match opt.command {
+        Command::Employee(EmployeeCommand::Profile(_)) | Command::Batch(_) => {}
+        _ if config.profile_format.is_some() => {
+            return Err(anyhow!(
+                "--format markdown, html and latex only apply to `employee profile`"
+            ))
+        }
+        _ => {}
+    }
+    let output = Output::new(config.format);
+    let mut uow = config.open_unit_of_work()?;
+    for recovered in uow.recovered_backups() {
+        eprintln!("Warning: {}", recovered);
+    }
+    match opt.command {
+        Command::Batch(batch_args) => {
+            let failed = batch::run(&batch_args, |words| {
+                let line = BatchLine::from_iter_safe(std::iter::once("batch".into()).chain(words))?;
+                // A failing command must not leave half of its changes or its result behind.
+                let snapshot = uow.snapshot();
+                if let Err(e) = execute(line.command, &mut uow, &output, &config) {
+                    uow.restore(snapshot);
+                    output.take_result();
+                    return Err(e);
+                }
+                Ok(output.take_result())
+            })?;
+            if failed > 0 && batch_args.all_or_nothing {
+                return Err(anyhow!("A command failed, no changes were saved"));
+            }
+            uow.commit()?;
+            if failed > 0 {
+                return Err(anyhow!("Commands failed: {}", failed));
+            }
+            Ok(())
+        }
+        command => {
+            execute(command, &mut uow, &output, &config)?;
+            uow.commit()
+        }
+    }
+}
+
+fn execute(command: Command, uow: &mut UnitOfWork, output: &Output, config: &Config) -> Result<()> {
+    match command {
+        Command::Skill(skill_command) => skill_op(skill_command, uow, output),
+        Command::Project(project_command) => project_op(project_command, uow, output),
+        Command::Employee(employee_command) => employee_op(employee_command, uow, output, config),
+        Command::Migrate { dry_run } => {
+            let migrations = if dry_run {
+                uow.pending_migrations()
+            } else {
+                uow.migrate()
+            };
+            output.list(&migrations)
+        }
+        Command::Import(import_args) => {
+            let report = transfer::import(&import_args, uow)?;
+            for rejected in &report.rejected {
+                eprintln!("Rejected line {}: {}", rejected.line, rejected.reason);
+            }
+            output.one(&report)
+        }
+        Command::Export(export_args) => transfer::export(&export_args, uow),
+        Command::Calendar(calendar_args) => {
+            let today = OffsetDateTime::now_utc().date();
+            let calendar =
+                calendar::export(&calendar_args, &*uow.employees, &*uow.projects, today)?;
+            match &calendar_args.file {
+                Some(path) if path.as_os_str() != "-" => fs::write(path, calendar)
+                    .with_context(|| format!("Failed to write {}", path.display())),
+                _ => output.document(calendar),
+            }
+        }
+        Command::Batch(_) => Err(anyhow!("Batches cannot be nested")),
+    }
 }
 
-fn skill_op(skill_command: SkillCommand, mut skill_db: FileBackedDb<SkillDb>) -> Result<()> {
+fn skill_op(skill_command: SkillCommand, uow: &mut UnitOfWork, output: &Output) -> Result<()> {
     match skill_command {
         SkillCommand::Add { label } => {
-            let added_skill = skill_db.db.add(label)?;
-            print_json(&added_skill)
+            let added_skill = uow.skills.add(label)?;
+            output.one(&added_skill)?
         }
-        SkillCommand::Get { id } => {
-            let skill = skill_db.db.get(id)?;
-            print_json(&skill)
+        SkillCommand::Get { skill } => {
+            let id = resolve::skill(&*uow.skills, &skill)?;
+            let skill = uow.skills.get(id)?;
+            output.optional(skill.as_ref())?
         }
-        SkillCommand::Find {} => {
-            let found = skill_db.db.find_skills()?;
-            print_json(&found)
+        SkillCommand::Find { args } => {
+            let found = find::skills(&*uow.skills, &args)?;
+            output.page(&found)?
         }
-        SkillCommand::Delete { id } => {
-            skill_db.db.delete(id.clone())?;
-            print_json(&format!("Deleted skill {}", id))
+        SkillCommand::Delete {
+            skill,
+            expected_version,
+        } => {
+            let id = resolve::skill(&*uow.skills, &skill)?;
+            uow.skills.delete(id.clone(), expected_version)?;
+            output.one(&Deleted::new("skill", id))?
         }
     }
-    skill_db.persist()
+    Ok(())
 }
 
 fn project_op(
     project_command: ProjectCommand,
-    mut project_db: FileBackedDb<ProjectDb>,
+    uow: &mut UnitOfWork,
+    output: &Output,
 ) -> Result<()> {
     match project_command {
         ProjectCommand::Add { label, description } => {
-            let added_project = project_db.db.add(label, description)?;
-            print_json(&added_project)
+            let added_project = uow.projects.add(label, description)?;
+            output.one(&added_project)?
         }
-        ProjectCommand::Delete { id } => {
-            project_db.db.delete(id.clone())?;
-            print_json(&format!("Deleted project {}", id))
+        ProjectCommand::Delete {
+            project,
+            expected_version,
+        } => {
+            let id = resolve::project(&*uow.projects, &project)?;
+            uow.projects.delete(id.clone(), expected_version)?;
+            output.one(&Deleted::new("project", id))?
+        }
+        ProjectCommand::Get { project } => {
+            let id = resolve::project(&*uow.projects, &project)?;
+            let project = uow.projects.get(id)?;
+            output.optional(project.as_ref())?
         }
-        ProjectCommand::Get { id } => {
-            let project = project_db.db.get(id)?;
-            print_json(&project)
+        ProjectCommand::Find { args } => {
+            let found = find::projects(&*uow.projects, &*uow.employees, &args)?;
+            output.page(&found)?
         }
     }
-    project_db.persist()
+    Ok(())
 }
 
 fn employee_op(
     employee_command: EmployeeCommand,
-    mut employee_db: FileBackedDb<EmployeeDb>,
-    project_db: FileBackedDb<ProjectDb>,
-    skill_db: FileBackedDb<SkillDb>,
+    uow: &mut UnitOfWork,
+    output: &Output,
+    config: &Config,
 ) -> Result<()> {
     match employee_command {
         EmployeeCommand::Add {
@@ -213,53 +345,119 @@ fn employee_op(
                 email: email.unwrap_or_else(|| EmailAddress(String::new())),
                 telephone: telephone.unwrap_or_else(|| TelephoneNumber(String::new())),
             };
-            let added = employee_db.db.add(add_employee_request)?;
-            print_json(&added)
+            let added = uow.employees.add(add_employee_request)?;
+            output.one(&added)?
+        }
+        EmployeeCommand::Delete {
+            employee,
+            expected_version,
+        } => {
+            let id = resolve::employee(&*uow.employees, &employee)?;
+            uow.employees.delete(id.clone(), expected_version)?;
+            output.one(&Deleted::new("employee", id))?
+        }
+        EmployeeCommand::Profile(profile_args) => {
+            let id = resolve::employee(&*uow.employees, &profile_args.employee)?;
+            let employee = uow
+                .employees
+                .get(id.clone())?
+                .ok_or_else(|| anyhow!("Employee {} not found", id))?;
+            let profile = profile::render(
+                &profile_args,
+                config.profile_format.unwrap_or(ProfileFormat::Markdown),
+                &employee,
+                config.templates_dir.as_deref(),
+            )?;
+            output.document(profile)?
+        }
+        EmployeeCommand::ExportResume(resume_args) => {
+            let id = resolve::employee(&*uow.employees, &resume_args.employee)?;
+            let employee = uow
+                .employees
+                .get(id.clone())?
+                .ok_or_else(|| anyhow!("Employee {} not found", id))?;
+            let resume = resume::export(&resume_args, &employee)?;
+            match &resume_args.file {
+                Some(path) if path.as_os_str() != "-" => fs::write(path, resume)
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                _ => output.document(resume)?,
+            }
+        }
+        EmployeeCommand::ImportResume(resume_args) => {
+            let report = resume::import(&resume_args, uow)?;
+            for rejected in &report.rejected {
+                eprintln!("Rejected {}", rejected.reason);
+            }
+            output.one(&report)?
+        }
+        EmployeeCommand::ExportVcard(vcard_args) => {
+            let vcards = vcard::export(&vcard_args, &*uow.employees)?;
+            match &vcard_args.file {
+                Some(path) if path.as_os_str() != "-" => fs::write(path, vcards)
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                _ => output.document(vcards)?,
+            }
+        }
+        EmployeeCommand::ImportVcard(vcard_args) => {
+            let report = vcard::import(&vcard_args, uow)?;
+            for rejected in &report.rejected {
+                eprintln!("Rejected line {}: {}", rejected.line, rejected.reason);
+            }
+            output.one(&report)?
         }
-        EmployeeCommand::Delete { id } => {
-            employee_db.db.delete(id.clone())?;
-            print_json(&format!("Deleted employee {}", id))
+        EmployeeCommand::Get { employee } => {
+            let id = resolve::employee(&*uow.employees, &employee)?;
+            let employee = uow.employees.get(id)?;
+            output.optional(employee.as_ref())?
         }
-        EmployeeCommand::Get { id } => {
-            let employee = employee_db.db.get(id)?;
-            print_json(&employee)
+        EmployeeCommand::Find { args } => {
+            let found = find::employees(&*uow.employees, &args)?;
+            output.page(&found)?
         }
         EmployeeCommand::AssignProject {
-            employee_id,
-            project_id,
+            employee,
+            project,
             start_date,
             end_date,
+            expected_version,
             contribution,
         } => {
-            let assigned = employee_db
-                .db
-                .with(&project_db.db)
+            let employee_id = resolve::employee(&*uow.employees, &employee)?;
+            let project_id = resolve::project(&*uow.projects, &project)?;
+            let assigned = uow
+                .employees
+                .with(&*uow.projects)
                 .create_project_assignment(ProjectAssignmentRequest {
                     employee_id,
                     project_id,
                     contribution,
                     start_date,
                     end_date,
+                    expected_version,
                 })?;
-            print_json(&assigned)
+            output.one(&assigned)?
         }
         EmployeeCommand::AssignSkill {
-            employee_id,
-            skill_id,
+            employee,
+            skill,
             skill_level,
             secret,
+            expected_version,
         } => {
-            let assigned = employee_db
-                .db
-                .with(&skill_db.db)
+            let employee_id = resolve::employee(&*uow.employees, &employee)?;
+            let skill_id = resolve::skill(&*uow.skills, &skill)?;
+            let assigned = uow
+                .employees
+                .with(&*uow.skills)
                 .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
                     employee_id,
                     skill_id,
                     level: skill_level,
                     secret,
+                    expected_version,
                 })?;
-            print_json(&assigned)
+            output.one(&assigned)?
         }
     }
-    employee_db.persist()
+    Ok(())
 }
@@ -0,0 +1,309 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use skill_manager::query::Page;
+use std::{cell::RefCell, io::Write, str::FromStr};
+
+mod records;
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Jsonl,
+    Csv,
+    Yaml,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            "yaml" => Ok(OutputFormat::Yaml),
+            _ => Err(anyhow!(
+                "Unknown format {}, expected table, json, jsonl, csv or yaml",
+                s
+            )),
+        }
+    }
+}
+
+/// A value that can be printed as a row of a table or CSV file.
+pub trait Record: Serialize {
+    fn header() -> &'static [&'static str];
+    fn row(&self) -> Vec<String>;
+}
+
+/// The result of deleting an entity.
+#[derive(Serialize)]
+pub struct Deleted {
+    pub deleted: &'static str,
+    pub id: String,
+}
+
+impl Deleted {
+    pub fn new(deleted: &'static str, id: impl ToString) -> Self {
+        Self {
+            deleted,
+            id: id.to_string(),
+        }
+    }
+}
+
+/// Prints command results to stdout in the configured format.
+pub struct Output {
+    format: OutputFormat,
+    /// The last printed result, which later commands of a batch can reference.
+    result: RefCell<Option<serde_json::Value>>,
+}
+
+impl Output {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            result: RefCell::new(None),
+        }
+    }
+
+    /// Returns the result printed since the last call.
+    pub fn take_result(&self) -> Option<serde_json::Value> {
+        self.result.borrow_mut().take()
+    }
+
+    /// Prints a rendered document as is.
+    pub fn document(&self, document: String) -> Result<()> {
+        self.record(&document)?;
+        self.print(document)
+    }
+
+    pub fn one<T: Record>(&self, record: &T) -> Result<()> {
+        self.record(record)?;
+        self.print(render(self.format, record, &[record])?)
+    }
+
+    /// Prints `null` in the serialization formats and no rows in table and CSV.
+    pub fn optional<T: Record>(&self, record: Option<&T>) -> Result<()> {
+        self.record(&record)?;
+        let records: Vec<_> = record.into_iter().collect();
+        self.print(render(self.format, &record, &records)?)
+    }
+
+    pub fn list<T: Record>(&self, records: &[T]) -> Result<()> {
+        self.record(&records)?;
+        self.print_list(records)
+    }
+
+    /// Prints a page of search results. JSON and YAML contain the whole page, the other formats
+    /// only its records and its position among all results is printed to stderr.
+    pub fn page<T: Record>(&self, page: &Page<T>) -> Result<()> {
+        self.record(page)?;
+        match self.format {
+            OutputFormat::Json | OutputFormat::Yaml => {
+                let rows: Vec<_> = page.items.iter().collect();
+                self.print(render(self.format, page, &rows)?)
+            }
+            _ => {
+                self.print_list(&page.items)?;
+                eprintln!("{}", position(page));
+                Ok(())
+            }
+        }
+    }
+
+    fn print_list<T: Record>(&self, records: &[T]) -> Result<()> {
+        let rows: Vec<_> = records.iter().collect();
+        match self.format {
+            OutputFormat::Jsonl => self.print(
+                records
+                    .iter()
+                    .map(|record| Ok(serde_json::to_string(record)? + "\n"))
+                    .collect::<Result<String>>()?,
+            ),
+            _ => self.print(render(self.format, &records, &rows)?),
+        }
+    }
+
+    fn record(&self, val: &impl Serialize) -> Result<()> {
+        *self.result.borrow_mut() = Some(serde_json::to_value(val)?);
+        Ok(())
+    }
+
+    fn print(&self, rendered: String) -> Result<()> {
+        let stdout = std::io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(rendered.as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// Describes which of the results are on the page, like `11–20 of 42`.
+fn position<T>(page: &Page<T>) -> String {
+    if page.items.is_empty() {
+        format!("0 of {}", page.total)
+    } else {
+        format!(
+            "{}–{} of {}",
+            page.offset + 1,
+            page.offset + page.items.len(),
+            page.total
+        )
+    }
+}
+
+/// Renders either the serializable value or the records, depending on the format.
+fn render<T: Record>(format: OutputFormat, val: &impl Serialize, records: &[&T]) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Json => serde_json::to_string_pretty(val)? + "\n",
+        OutputFormat::Jsonl => serde_json::to_string(val)? + "\n",
+        OutputFormat::Yaml => serde_yaml::to_string(val)?.trim_end().to_string() + "\n",
+        OutputFormat::Table => render_table(T::header(), records),
+        OutputFormat::Csv => render_csv(T::header(), records)?,
+    })
+}
+
+fn render_table<T: Record>(header: &[&str], records: &[&T]) -> String {
+    let header: Vec<String> = header.iter().map(|column| column.to_uppercase()).collect();
+    let rows: Vec<Vec<String>> = records.iter().map(|record| record.row()).collect();
+    let mut widths: Vec<usize> = header.iter().map(|column| column.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        table.push_str(line.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+fn render_csv<T: Record>(header: &[&str], records: &[&T]) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(header)?;
+    for record in records {
+        writer.write_record(record.row())?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::query::PageRequest;
+
+    #[derive(Serialize)]
+    struct TestRecord {
+        name: String,
+        level: usize,
+    }
+
+    impl Record for TestRecord {
+        fn header() -> &'static [&'static str] {
+            &["name", "level"]
+        }
+
+        fn row(&self) -> Vec<String> {
+            vec![self.name.clone(), self.level.to_string()]
+        }
+    }
+
+    fn records() -> Vec<TestRecord> {
+        vec![
+            TestRecord {
+                name: "Rust".into(),
+                level: 5,
+            },
+            TestRecord {
+                name: "Java, Kotlin".into(),
+                level: 10,
+            },
+        ]
+    }
+
+    fn render_list(format: OutputFormat) -> Result<String> {
+        let records = records();
+        let rows: Vec<_> = records.iter().collect();
+        render(format, &records, &rows)
+    }
+
+    #[test]
+    fn table_test() -> Result<()> {
+        assert_eq!(
+            render_list(OutputFormat::Table)?,
+            "NAME          LEVEL\nRust          5\nJava, Kotlin  10\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn csv_test() -> Result<()> {
+        assert_eq!(
+            render_list(OutputFormat::Csv)?,
+            "name,level\nRust,5\n\"Java, Kotlin\",10\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn page_test() -> Result<()> {
+        let page = Page::paginate(
+            records(),
+            PageRequest {
+                offset: 1,
+                limit: Some(1),
+            },
+        );
+        assert_eq!(position(&page), "2–2 of 2");
+        let rows: Vec<_> = page.items.iter().collect();
+        let json: serde_json::Value =
+            serde_json::from_str(&render(OutputFormat::Json, &page, &rows)?)?;
+        assert_eq!(json["items"][0]["name"], "Java, Kotlin");
+        assert_eq!(
+            (json["offset"].as_u64(), json["total"].as_u64()),
+            (Some(1), Some(2))
+        );
+
+        let page = Page::paginate(
+            records(),
+            PageRequest {
+                offset: 2,
+                limit: None,
+            },
+        );
+        assert_eq!(position(&page), "0 of 2");
+        Ok(())
+    }
+
+    #[test]
+    fn serialization_formats_test() -> Result<()> {
+        let json: serde_json::Value = serde_json::from_str(&render_list(OutputFormat::Json)?)?;
+        assert_eq!(json[1]["name"], "Java, Kotlin");
+
+        let yaml: serde_json::Value = serde_yaml::from_str(&render_list(OutputFormat::Yaml)?)?;
+        assert_eq!(yaml, json);
+
+        let none: Option<&TestRecord> = None;
+        assert_eq!(
+            render::<TestRecord>(OutputFormat::Jsonl, &none, &[])?,
+            "null\n"
+        );
+        assert_eq!(
+            render::<TestRecord>(OutputFormat::Table, &none, &[])?,
+            "NAME  LEVEL\n"
+        );
+        Ok(())
+    }
+}
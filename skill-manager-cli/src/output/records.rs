@@ -0,0 +1,149 @@
+use super::{Deleted, Record};
+use skill_manager::{
+    employees::{Employee, ProjectAssignment, SkillAssignment},
+    import::ImportReport,
+    projects::Project,
+    skills::Skill,
+};
+use skill_manager_file::schema::PendingMigration;
+use time::Date;
+
+impl Record for Skill {
+    fn header() -> &'static [&'static str] {
+        &["id", "label", "version"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.label.to_string(),
+            self.version.to_string(),
+        ]
+    }
+}
+
+impl Record for Project {
+    fn header() -> &'static [&'static str] {
+        &["id", "label", "description", "version"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.label.to_string(),
+            self.description.to_string(),
+            self.version.to_string(),
+        ]
+    }
+}
+
+impl Record for Employee {
+    fn header() -> &'static [&'static str] {
+        &[
+            "id",
+            "first name",
+            "last name",
+            "title",
+            "email",
+            "telephone",
+            "skills",
+            "projects",
+            "last update",
+            "version",
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        let skills: Vec<String> = self
+            .skills
+            .iter()
+            .map(|skill| format!("{}:{}", skill.label, skill.level))
+            .collect();
+        let projects: Vec<String> = self.projects.iter().map(|p| p.label.to_string()).collect();
+        vec![
+            self.id.to_string(),
+            self.first_name.to_string(),
+            self.last_name.to_string(),
+            self.title.to_string(),
+            self.email.to_string(),
+            self.telephone.to_string(),
+            skills.join(", "),
+            projects.join(", "),
+            self.last_update.format("%F %T"),
+            self.version.to_string(),
+        ]
+    }
+}
+
+impl Record for SkillAssignment {
+    fn header() -> &'static [&'static str] {
+        &["skill", "level", "secret"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.label.to_string(),
+            self.level.to_string(),
+            self.secret.to_string(),
+        ]
+    }
+}
+
+impl Record for ProjectAssignment {
+    fn header() -> &'static [&'static str] {
+        &["id", "project", "contribution", "start date", "end date"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.label.to_string(),
+            self.contribution.to_string(),
+            format_date(self.start_date),
+            self.end_date.map(format_date).unwrap_or_default(),
+        ]
+    }
+}
+
+impl Record for Deleted {
+    fn header() -> &'static [&'static str] {
+        &["deleted", "id"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![self.deleted.to_string(), self.id.clone()]
+    }
+}
+
+impl Record for PendingMigration {
+    fn header() -> &'static [&'static str] {
+        &["collection", "from version", "to version"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.collection.to_string(),
+            self.from_version.to_string(),
+            self.to_version.to_string(),
+        ]
+    }
+}
+
+impl Record for ImportReport {
+    fn header() -> &'static [&'static str] {
+        &["created", "updated", "unchanged", "rejected"]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.created.to_string(),
+            self.updated.to_string(),
+            self.unchanged.to_string(),
+            self.rejected.len().to_string(),
+        ]
+    }
+}
+
+fn format_date(date: Date) -> String {
+    date.format("%F")
+}
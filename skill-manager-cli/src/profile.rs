@@ -0,0 +1,388 @@
+use anyhow::{anyhow, Context, Result};
+use handlebars::Handlebars;
+use serde::Serialize;
+use skill_manager::employees::Employee;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+const TEMPLATE_NAME: &str = "profile";
+
+/// Renders the profile of an employee with the template for the document format given with
+/// `--format`: markdown (the default), html or latex.
+///
+/// The template is the one given with `--template`, `profile.<md|html|tex>.hbs` from the
+/// `templates` directory next to the config file, or the built-in one. Templates use the
+/// Handlebars syntax, values are escaped for the output format and consecutive blank lines
+/// are collapsed into one.
+#[derive(StructOpt)]
+pub struct ProfileArgs {
+    pub employee: String,
+    /// A Handlebars template to use instead of the configured one
+    #[structopt(long = "template", parse(from_os_str))]
+    template: Option<PathBuf>,
+    /// Includes the skills that are marked as secret
+    #[structopt(long = "include-secret")]
+    include_secret: bool,
+}
+
+/// The document formats of profiles, given with the global `--format`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ProfileFormat {
+    Markdown,
+    Html,
+    Latex,
+}
+
+impl FromStr for ProfileFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "markdown" => Ok(ProfileFormat::Markdown),
+            "html" => Ok(ProfileFormat::Html),
+            "latex" => Ok(ProfileFormat::Latex),
+            _ => Err(anyhow!(
+                "Unknown profile format {}, expected markdown, html or latex",
+                s
+            )),
+        }
+    }
+}
+
+impl ProfileFormat {
+    fn template_file_name(self) -> &'static str {
+        match self {
+            ProfileFormat::Markdown => "profile.md.hbs",
+            ProfileFormat::Html => "profile.html.hbs",
+            ProfileFormat::Latex => "profile.tex.hbs",
+        }
+    }
+
+    fn builtin_template(self) -> &'static str {
+        match self {
+            ProfileFormat::Markdown => include_str!("../templates/profile.md.hbs"),
+            ProfileFormat::Html => include_str!("../templates/profile.html.hbs"),
+            ProfileFormat::Latex => include_str!("../templates/profile.tex.hbs"),
+        }
+    }
+
+    fn escape(self, s: &str) -> String {
+        match self {
+            ProfileFormat::Markdown => escape_markdown(s),
+            ProfileFormat::Html => handlebars::html_escape(s),
+            ProfileFormat::Latex => escape_latex(s),
+        }
+    }
+}
+
+/// The values available in profile templates.
+#[derive(Serialize)]
+struct ProfileContext<'a> {
+    first_name: &'a str,
+    last_name: &'a str,
+    title: &'a str,
+    email: &'a str,
+    telephone: &'a str,
+    /// Ordered by descending level.
+    skills: Vec<SkillContext<'a>>,
+    /// Ordered by descending start date.
+    projects: Vec<ProjectContext<'a>>,
+}
+
+#[derive(Serialize)]
+struct SkillContext<'a> {
+    label: &'a str,
+    level: usize,
+}
+
+#[derive(Serialize)]
+struct ProjectContext<'a> {
+    label: &'a str,
+    description: &'a str,
+    contribution: &'a str,
+    start_date: String,
+    end_date: Option<String>,
+}
+
+impl<'a> ProfileContext<'a> {
+    fn new(employee: &'a Employee, include_secret: bool) -> Self {
+        let mut skills: Vec<_> = employee
+            .skills
+            .iter()
+            .filter(|skill| include_secret || !skill.secret)
+            .collect();
+        skills.sort_by(|a, b| b.level.cmp(&a.level).then_with(|| a.label.cmp(&b.label)));
+        let mut projects: Vec<_> = employee.projects.iter().collect();
+        projects.sort_by_key(|project| std::cmp::Reverse(project.start_date));
+
+        Self {
+            first_name: &employee.first_name.0,
+            last_name: &employee.last_name.0,
+            title: &employee.title.0,
+            email: &employee.email.0,
+            telephone: &employee.telephone.0,
+            skills: skills
+                .into_iter()
+                .map(|skill| SkillContext {
+                    label: &skill.label.0,
+                    level: skill.level.0,
+                })
+                .collect(),
+            projects: projects
+                .into_iter()
+                .map(|project| ProjectContext {
+                    label: &project.label.0,
+                    description: &project.description.0,
+                    contribution: &project.contribution.0,
+                    start_date: project.start_date.format("%F"),
+                    end_date: project.end_date.map(|date| date.format("%F")),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn render(
+    args: &ProfileArgs,
+    format: ProfileFormat,
+    employee: &Employee,
+    templates_dir: Option<&Path>,
+) -> Result<String> {
+    let configured_template = templates_dir
+        .map(|dir| dir.join(format.template_file_name()))
+        .filter(|path| path.exists());
+    let template = match args.template.as_ref().or(configured_template.as_ref()) {
+        Some(path) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read template {}", path.display()))?,
+        None => format.builtin_template().to_string(),
+    };
+    render_template(
+        &template,
+        format,
+        &ProfileContext::new(employee, args.include_secret),
+    )
+}
+
+fn render_template(
+    template: &str,
+    format: ProfileFormat,
+    context: &ProfileContext,
+) -> Result<String> {
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    handlebars.register_escape_fn(move |s| format.escape(s));
+    handlebars
+        .register_template_string(TEMPLATE_NAME, template)
+        .context("Invalid template")?;
+    let rendered = handlebars.render(TEMPLATE_NAME, context)?;
+    Ok(collapse_blank_lines(&rendered))
+}
+
+/// Collapses the runs of blank lines that block helpers on lines of their own leave behind.
+fn collapse_blank_lines(s: &str) -> String {
+    let mut lines: Vec<&str> = vec![];
+    let mut previous_blank = true;
+    for line in s.split('\n') {
+        let blank = line.trim().is_empty();
+        if !(blank && previous_blank) {
+            lines.push(if blank { "" } else { line });
+        }
+        previous_blank = blank;
+    }
+    lines.join("\n")
+}
+
+fn escape_markdown(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\`*_[]<>|#".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn escape_latex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{
+            EmailAddress, FirstName, LastName, ProjectAssignment, ProjectAssignmentId,
+            ProjectContribution, SkillAssignment, SkillLevel, TelephoneNumber, Title,
+        },
+        projects::{ProjectDescription, ProjectLabel},
+        skills::SkillLabel,
+        version::Version,
+    };
+    use time::{date, OffsetDateTime};
+    use uuid::Uuid;
+
+    fn employee() -> Employee {
+        Employee {
+            id: "9f6d3c1e-2b8a-4a8e-9c56-3f1e0d7b2a41".parse().unwrap(),
+            first_name: FirstName("Ada".into()),
+            last_name: LastName("Lovelace".into()),
+            title: Title("R&D Consultant".into()),
+            email: EmailAddress("ada@example.com".into()),
+            telephone: TelephoneNumber("".into()),
+            skills: vec![
+                SkillAssignment {
+                    label: SkillLabel("C#".into()),
+                    level: SkillLevel(2),
+                    secret: true,
+                },
+                SkillAssignment {
+                    label: SkillLabel("Rust".into()),
+                    level: SkillLevel(4),
+                    secret: false,
+                },
+            ],
+            projects: vec![ProjectAssignment {
+                id: ProjectAssignmentId(Uuid::nil()),
+                label: ProjectLabel("Skill Manager".into()),
+                description: ProjectDescription("Internal <tool>".into()),
+                contribution: ProjectContribution("Development".into()),
+                start_date: date!(2020 - 01 - 01),
+                end_date: None,
+            }],
+            last_update: OffsetDateTime::now_utc(),
+            version: Version::default(),
+        }
+    }
+
+    fn args(template: Option<PathBuf>, include_secret: bool) -> ProfileArgs {
+        ProfileArgs {
+            employee: "ada".into(),
+            template,
+            include_secret,
+        }
+    }
+
+    #[test]
+    fn markdown_profile_test() -> Result<()> {
+        let profile = render(
+            &args(None, false),
+            ProfileFormat::Markdown,
+            &employee(),
+            None,
+        )?;
+
+        assert_eq!(
+            profile,
+            "# Ada Lovelace\n\
+             \n\
+             *R&D Consultant*\n\
+             \n\
+             - Email: ada@example.com\n\
+             \n\
+             ## Skills\n\
+             \n\
+             - Rust (4)\n\
+             \n\
+             ## Projects\n\
+             \n\
+             ### Skill Manager\n\
+             \n\
+             2020-01-01 to today\n\
+             \n\
+             Internal \\<tool\\>\n\
+             \n\
+             Development\n"
+        );
+
+        let profile = render(
+            &args(None, true),
+            ProfileFormat::Markdown,
+            &employee(),
+            None,
+        )?;
+        assert!(profile.contains("- Rust (4)\n- C\\# (2)\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn escapes_values_test() -> Result<()> {
+        let html = render(&args(None, true), ProfileFormat::Html, &employee(), None)?;
+        assert!(html.contains("<p><em>R&amp;D Consultant</em></p>"));
+        assert!(html.contains("<p>Internal &lt;tool&gt;</p>"));
+
+        let latex = render(&args(None, true), ProfileFormat::Latex, &employee(), None)?;
+        assert!(latex.contains("\\textit{R\\&D Consultant}"));
+        assert!(latex.contains("\\item C\\# (2)"));
+        Ok(())
+    }
+
+    #[test]
+    fn latex_profile_without_skills_or_contact_test() -> Result<()> {
+        let latex = render(&args(None, true), ProfileFormat::Latex, &employee(), None)?;
+        assert!(latex.contains("\\begin{itemize}\n\\item Email: ada@example.com\n"));
+
+        let mut employee = employee();
+        employee.skills.retain(|skill| skill.secret);
+        employee.email = EmailAddress("".into());
+        let latex = render(&args(None, false), ProfileFormat::Latex, &employee, None)?;
+        assert!(!latex.contains("itemize"));
+        assert!(!latex.contains("\\subsection*{Skills}"));
+        assert!(latex.contains("\\subsection*{Projects}"));
+        Ok(())
+    }
+
+    #[test]
+    fn user_defined_template_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("profile.html.hbs"),
+            "{{first_name}}: {{#each skills}}{{label}} {{/each}}",
+        )?;
+        let template = dir.path().join("custom.hbs");
+        fs::write(&template, "{{last_name}}")?;
+
+        let profile = render(
+            &args(None, false),
+            ProfileFormat::Html,
+            &employee(),
+            Some(dir.path()),
+        )?;
+        assert_eq!(profile, "Ada: Rust ");
+
+        let profile = render(
+            &args(Some(template), false),
+            ProfileFormat::Html,
+            &employee(),
+            Some(dir.path()),
+        )?;
+        assert_eq!(profile, "Lovelace");
+
+        fs::write(dir.path().join("profile.html.hbs"), "{{unknown}}")?;
+        assert!(render(
+            &args(None, false),
+            ProfileFormat::Html,
+            &employee(),
+            Some(dir.path())
+        )
+        .is_err());
+        Ok(())
+    }
+}
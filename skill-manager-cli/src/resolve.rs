@@ -0,0 +1,239 @@
+use skill_manager::{
+    employees::{usecase::FindEmployees, EmployeeId},
+    projects::{usecase::FindProjects, ProjectId},
+    skills::{usecase::FindSkills, SkillId},
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ResolveError {
+    #[error("No {kind} matches '{reference}'")]
+    NotFound {
+        kind: &'static str,
+        reference: String,
+    },
+    #[error("'{reference}' matches more than one {kind}:\n{}", .candidates.join("\n"))]
+    Ambiguous {
+        kind: &'static str,
+        reference: String,
+        candidates: Vec<String>,
+    },
+}
+
+/// An entity that a reference can resolve to.
+struct Candidate<Id> {
+    id: Id,
+    uuid: Uuid,
+    /// Labels, names or email addresses that match the reference exactly, ignoring case.
+    names: Vec<String>,
+    description: String,
+}
+
+/// Resolves a full UUID, a skill label or an unambiguous UUID prefix to a skill id.
+pub fn skill(skills: &impl FindSkills, reference: &str) -> anyhow::Result<SkillId> {
+    if let Ok(id) = reference.parse() {
+        return Ok(id);
+    }
+    let candidates = skills
+        .find_skills(&Default::default())?
+        .items
+        .into_iter()
+        .map(|skill| Candidate {
+            uuid: skill.id.0,
+            names: vec![skill.label.0.clone()],
+            description: format!("{}  {}", skill.id, skill.label),
+            id: skill.id,
+        });
+    Ok(resolve("skill", reference, candidates)?)
+}
+
+/// Resolves a full UUID, a project label or an unambiguous UUID prefix to a project id.
+pub fn project(projects: &impl FindProjects, reference: &str) -> anyhow::Result<ProjectId> {
+    if let Ok(id) = reference.parse() {
+        return Ok(id);
+    }
+    let candidates = projects
+        .find_projects(&Default::default())?
+        .items
+        .into_iter()
+        .map(|project| Candidate {
+            uuid: project.id.0,
+            names: vec![project.label.0.clone()],
+            description: format!("{}  {}", project.id, project.label),
+            id: project.id,
+        });
+    Ok(resolve("project", reference, candidates)?)
+}
+
+/// Resolves a full UUID, an employee's full name or email address or an unambiguous UUID
+/// prefix to an employee id.
+pub fn employee(employees: &impl FindEmployees, reference: &str) -> anyhow::Result<EmployeeId> {
+    if let Ok(id) = reference.parse() {
+        return Ok(id);
+    }
+    let candidates = employees
+        .find_employees(&Default::default())?
+        .items
+        .into_iter()
+        .map(|employee| Candidate {
+            uuid: employee.id.0,
+            names: vec![
+                format!("{} {}", employee.first_name, employee.last_name),
+                employee.email.0.clone(),
+            ],
+            description: format!(
+                "{}  {} {} <{}>",
+                employee.id, employee.first_name, employee.last_name, employee.email
+            ),
+            id: employee.id,
+        });
+    Ok(resolve("employee", reference, candidates)?)
+}
+
+/// Exact name matches take precedence over UUID prefix matches.
+fn resolve<Id>(
+    kind: &'static str,
+    reference: &str,
+    candidates: impl Iterator<Item = Candidate<Id>>,
+) -> Result<Id, ResolveError> {
+    let reference_lowercase = reference.to_lowercase();
+    let mut name_matches = vec![];
+    let mut prefix_matches = vec![];
+    for candidate in candidates {
+        if candidate
+            .names
+            .iter()
+            .any(|name| name.to_lowercase() == reference_lowercase)
+        {
+            name_matches.push(candidate);
+        } else if candidate
+            .uuid
+            .to_hyphenated()
+            .to_string()
+            .starts_with(&reference_lowercase)
+        {
+            prefix_matches.push(candidate);
+        }
+    }
+
+    let mut matches = if name_matches.is_empty() {
+        prefix_matches
+    } else {
+        name_matches
+    };
+    match matches.len() {
+        0 => Err(ResolveError::NotFound {
+            kind,
+            reference: reference.to_string(),
+        }),
+        1 => Ok(matches.remove(0).id),
+        _ => {
+            let mut candidates: Vec<_> = matches.into_iter().map(|c| c.description).collect();
+            candidates.sort();
+            Err(ResolveError::Ambiguous {
+                kind,
+                reference: reference.to_string(),
+                candidates,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{
+            usecase::EmployeeQuery, EmailAddress, Employee, FirstName, LastName, TelephoneNumber,
+            Title,
+        },
+        query::{Page, PageRequest},
+        skills::{usecase::SkillQuery, Skill, SkillLabel},
+        version::Version,
+    };
+
+    fn skill(id: &str, label: &str) -> Skill {
+        Skill {
+            id: id.parse().unwrap(),
+            label: SkillLabel(label.into()),
+            version: Version::default(),
+        }
+    }
+
+    fn skills() -> Vec<Skill> {
+        vec![
+            skill("0b5e5d3a-6c1f-4c2b-8f7e-1a2b3c4d5e6f", "Rust"),
+            skill("0b5e9999-1f3b-4e6d-9a8c-7b6a5f4e3d2c", "Java"),
+            skill("5d2c8e7a-1f3b-4e6d-9a8c-7b6a5f4e3d2c", "0b5e"),
+        ]
+    }
+
+    fn resolve_skill(reference: &str) -> Result<SkillId, ResolveError> {
+        let skills = skills();
+        super::skill(
+            &|_: &SkillQuery| Ok(Page::paginate(skills.clone(), PageRequest::default())),
+            reference,
+        )
+        .map_err(|e| e.downcast::<ResolveError>().unwrap())
+    }
+
+    #[test]
+    fn resolve_skill_test() {
+        let skills = skills();
+
+        assert_eq!(resolve_skill("rust"), Ok(skills[0].id.clone()));
+        assert_eq!(resolve_skill("0B5E5"), Ok(skills[0].id.clone()));
+        assert_eq!(resolve_skill("0b5e"), Ok(skills[2].id.clone()));
+        assert_eq!(
+            resolve_skill("aaaaaaaa-1f3b-4e6d-9a8c-7b6a5f4e3d2c"),
+            Ok("aaaaaaaa-1f3b-4e6d-9a8c-7b6a5f4e3d2c".parse().unwrap())
+        );
+        assert_eq!(
+            resolve_skill("Go"),
+            Err(ResolveError::NotFound {
+                kind: "skill",
+                reference: "Go".into()
+            })
+        );
+        assert_eq!(
+            resolve_skill("0b5"),
+            Err(ResolveError::Ambiguous {
+                kind: "skill",
+                reference: "0b5".into(),
+                candidates: vec![
+                    "0b5e5d3a-6c1f-4c2b-8f7e-1a2b3c4d5e6f  Rust".into(),
+                    "0b5e9999-1f3b-4e6d-9a8c-7b6a5f4e3d2c  Java".into(),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn resolve_employee_test() -> anyhow::Result<()> {
+        let employee = Employee {
+            id: "9f6d3c1e-2b8a-4a8e-9c56-3f1e0d7b2a41".parse()?,
+            first_name: FirstName("Ada".into()),
+            last_name: LastName("Lovelace".into()),
+            title: Title("".into()),
+            email: EmailAddress("ada@example.com".into()),
+            telephone: TelephoneNumber("".into()),
+            skills: vec![],
+            projects: vec![],
+            last_update: time::OffsetDateTime::now_utc(),
+            version: Version::default(),
+        };
+        let employees = |_: &EmployeeQuery| {
+            Ok(Page::paginate(
+                vec![employee.clone()],
+                PageRequest::default(),
+            ))
+        };
+
+        assert_eq!(super::employee(&employees, "ada lovelace")?, employee.id);
+        assert_eq!(super::employee(&employees, "ADA@example.com")?, employee.id);
+        assert!(super::employee(&employees, "Ada").is_err());
+
+        Ok(())
+    }
+}
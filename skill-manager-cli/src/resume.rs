@@ -0,0 +1,538 @@
+use crate::transfer::{self, Kind};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use skill_manager::{
+    employees::Employee,
+    import::{ImportReport, RejectedRow, Row},
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+use time::Date;
+
+/// Exports the profile of an employee as a CV in a standard format: JSON Resume
+/// (https://jsonresume.org) or Europass XML.
+///
+/// Project assignments become work entries with the project as employer and the
+/// contribution as position.
+#[derive(StructOpt)]
+pub struct ExportResumeArgs {
+    pub employee: String,
+    /// One of json-resume or europass
+    #[structopt(long = "standard", default_value = "json-resume")]
+    standard: Standard,
+    /// Includes the skills that are marked as secret
+    #[structopt(long = "include-secret")]
+    include_secret: bool,
+    /// The file to write, stdout if omitted
+    #[structopt(parse(from_os_str))]
+    pub file: Option<PathBuf>,
+}
+
+/// Imports a JSON Resume, creating or updating the employee with its email address.
+///
+/// Unknown skills and projects are added. Skill levels are numbers or one of beginner,
+/// intermediate, advanced, expert and master, imported skills are never secret.
+#[derive(StructOpt)]
+pub struct ImportResumeArgs {
+    /// The JSON Resume file, `-` reads it from stdin
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+    /// Reports what would be imported without saving it
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Standard {
+    JsonResume,
+    Europass,
+}
+
+impl FromStr for Standard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json-resume" => Ok(Standard::JsonResume),
+            "europass" => Ok(Standard::Europass),
+            _ => Err(anyhow!(
+                "Unknown standard {}, expected json-resume or europass",
+                s
+            )),
+        }
+    }
+}
+
+/// The subset of the JSON Resume schema that maps to employees.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Resume {
+    basics: Basics,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    work: Vec<Work>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skills: Vec<ResumeSkill>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Basics {
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    label: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    email: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    phone: String,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Work {
+    name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    position: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    summary: String,
+    start_date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_date: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct ResumeSkill {
+    name: String,
+    level: String,
+}
+
+impl Resume {
+    fn new(employee: &Employee, include_secret: bool) -> Self {
+        Resume {
+            basics: Basics {
+                name: format!("{} {}", employee.first_name, employee.last_name),
+                label: employee.title.0.clone(),
+                email: employee.email.0.clone(),
+                phone: employee.telephone.0.clone(),
+            },
+            work: employee
+                .projects
+                .iter()
+                .map(|project| Work {
+                    name: project.label.0.clone(),
+                    position: project.contribution.0.clone(),
+                    summary: project.description.0.clone(),
+                    start_date: project.start_date.format("%F"),
+                    end_date: project.end_date.map(|date| date.format("%F")),
+                })
+                .collect(),
+            skills: employee
+                .skills
+                .iter()
+                .filter(|skill| include_secret || !skill.secret)
+                .map(|skill| ResumeSkill {
+                    name: skill.label.0.clone(),
+                    level: skill.level.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn export(args: &ExportResumeArgs, employee: &Employee) -> Result<String> {
+    match args.standard {
+        Standard::JsonResume => {
+            Ok(serde_json::to_string_pretty(&Resume::new(employee, args.include_secret))? + "\n")
+        }
+        Standard::Europass => Ok(europass(employee, args.include_secret)),
+    }
+}
+
+/// Renders a Europass CV in the XML format of the Europass skills passport.
+fn europass(employee: &Employee, include_secret: bool) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(
+        "<SkillsPassport xmlns=\"http://europass.cedefop.europa.eu/Europass\" locale=\"en\">\n",
+    );
+    xml.push_str("  <LearnerInfo>\n    <Identification>\n      <PersonName>\n");
+    element(&mut xml, 8, "FirstName", &employee.first_name.0);
+    element(&mut xml, 8, "Surname", &employee.last_name.0);
+    xml.push_str("      </PersonName>\n      <ContactInfo>\n");
+    if !employee.email.0.is_empty() {
+        xml.push_str("        <Email>\n");
+        element(&mut xml, 10, "Contact", &employee.email.0);
+        xml.push_str("        </Email>\n");
+    }
+    if !employee.telephone.0.is_empty() {
+        xml.push_str("        <TelephoneList>\n          <Telephone>\n");
+        element(&mut xml, 12, "Contact", &employee.telephone.0);
+        xml.push_str("          </Telephone>\n        </TelephoneList>\n");
+    }
+    xml.push_str("      </ContactInfo>\n    </Identification>\n");
+    if !employee.title.0.is_empty() {
+        xml.push_str("    <Headline>\n      <Type>\n");
+        element(&mut xml, 8, "Code", "position");
+        element(&mut xml, 8, "Label", "Position");
+        xml.push_str("      </Type>\n      <Description>\n");
+        element(&mut xml, 8, "Label", &employee.title.0);
+        xml.push_str("      </Description>\n    </Headline>\n");
+    }
+    if !employee.projects.is_empty() {
+        xml.push_str("    <WorkExperienceList>\n");
+        for project in &employee.projects {
+            xml.push_str("      <WorkExperience>\n        <Period>\n");
+            xml.push_str(&format!(
+                "          <From {}/>\n",
+                europass_date(project.start_date)
+            ));
+            match project.end_date {
+                Some(end_date) => {
+                    xml.push_str(&format!("          <To {}/>\n", europass_date(end_date)))
+                }
+                None => element(&mut xml, 10, "Current", "true"),
+            }
+            xml.push_str("        </Period>\n        <Position>\n");
+            element(&mut xml, 10, "Label", &project.contribution.0);
+            xml.push_str("        </Position>\n");
+            element(&mut xml, 8, "Activities", &project.description.0);
+            xml.push_str("        <Employer>\n");
+            element(&mut xml, 10, "Name", &project.label.0);
+            xml.push_str("        </Employer>\n      </WorkExperience>\n");
+        }
+        xml.push_str("    </WorkExperienceList>\n");
+    }
+    let skills: Vec<_> = employee
+        .skills
+        .iter()
+        .filter(|skill| include_secret || !skill.secret)
+        .map(|skill| format!("{} ({})", skill.label, skill.level))
+        .collect();
+    if !skills.is_empty() {
+        xml.push_str("    <Skills>\n      <Computer>\n");
+        element(&mut xml, 8, "Description", &skills.join(", "));
+        xml.push_str("      </Computer>\n    </Skills>\n");
+    }
+    xml.push_str("  </LearnerInfo>\n</SkillsPassport>\n");
+    xml
+}
+
+fn element(xml: &mut String, indent: usize, name: &str, text: &str) {
+    xml.push_str(&format!(
+        "{:indent$}<{name}>{}</{name}>\n",
+        "",
+        escape_xml(text),
+        indent = indent,
+        name = name
+    ));
+}
+
+fn europass_date(date: Date) -> String {
+    format!(
+        "year=\"{}\" month=\"--{:02}\" day=\"---{:02}\"",
+        date.year(),
+        date.month(),
+        date.day()
+    )
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn import(args: &ImportResumeArgs, uow: &mut UnitOfWork) -> Result<ImportReport> {
+    let resume = read_resume(&args.file)?;
+    if args.dry_run {
+        import_resume(&resume, &mut uow.detached())
+    } else {
+        import_resume(&resume, uow)
+    }
+}
+
+fn read_resume(path: &Path) -> Result<Resume> {
+    let mut contents = String::new();
+    if path.as_os_str() == "-" {
+        io::stdin().read_to_string(&mut contents)?;
+    } else {
+        contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+    }
+    serde_json::from_str(&contents).context("Invalid JSON Resume")
+}
+
+/// Imports the resume with the CSV import by mapping each of its parts to rows. The rows are
+/// numbered by their position in the part of the resume they come from.
+fn import_resume(resume: &Resume, uow: &mut UnitOfWork) -> Result<ImportReport> {
+    let basics = &resume.basics;
+    if basics.email.trim().is_empty() {
+        return Err(anyhow!(
+            "The resume has no email address to match the employee"
+        ));
+    }
+    let (first_name, last_name) = split_name(&basics.name)?;
+    let employee = row(
+        1,
+        &[
+            ("first_name", first_name),
+            ("last_name", last_name),
+            ("title", &basics.label),
+            ("email", &basics.email),
+            ("telephone", &basics.phone),
+        ],
+    );
+    let mut report = transfer::import_rows(Kind::Employees, &[employee], uow)?;
+    if !report.rejected.is_empty() {
+        return Err(anyhow!("Invalid basics: {}", report.rejected[0].reason));
+    }
+
+    let skills: Vec<_> = numbered(&resume.skills, |skill| {
+        vec![
+            ("label", skill.name.clone()),
+            ("email", basics.email.clone()),
+            ("skill", skill.name.clone()),
+            ("level", parse_level(&skill.level)),
+        ]
+    });
+    let projects: Vec<_> = numbered(&resume.work, |work| {
+        vec![
+            ("label", work.name.clone()),
+            ("description", work.summary.clone()),
+            ("email", basics.email.clone()),
+            ("project", work.name.clone()),
+            ("contribution", work.position.clone()),
+            ("start_date", complete_date(&work.start_date)),
+            (
+                "end_date",
+                work.end_date
+                    .as_deref()
+                    .map(complete_date)
+                    .unwrap_or_default(),
+            ),
+        ]
+    });
+    for &(kind, rows, part) in &[
+        (Kind::Skills, &skills, "skills"),
+        (Kind::SkillAssignments, &skills, "skills"),
+        (Kind::Projects, &projects, "work"),
+        (Kind::ProjectAssignments, &projects, "work"),
+    ] {
+        let imported = transfer::import_rows(kind, rows, uow)?;
+        merge(&mut report, imported, part);
+    }
+    Ok(report)
+}
+
+fn row(line: usize, columns: &[(&str, &str)]) -> Row {
+    Row {
+        line,
+        columns: columns
+            .iter()
+            .map(|(column, value)| (column.to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+fn numbered<T>(entries: &[T], columns: impl Fn(&T) -> Vec<(&'static str, String)>) -> Vec<Row> {
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| Row {
+            line: index + 1,
+            columns: columns(entry)
+                .into_iter()
+                .map(|(column, value)| (column.to_string(), value))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Adds the counts of an import step and names the part of the resume in its rejections.
+fn merge(report: &mut ImportReport, imported: ImportReport, part: &str) {
+    report.created += imported.created;
+    report.updated += imported.updated;
+    report.unchanged += imported.unchanged;
+    report
+        .rejected
+        .extend(imported.rejected.into_iter().map(|rejected| RejectedRow {
+            line: rejected.line,
+            reason: format!("{} entry {}: {}", part, rejected.line, rejected.reason),
+        }));
+}
+
+/// Splits a full name at its last space. Employees need both names, so a single word is
+/// rejected.
+fn split_name(name: &str) -> Result<(&str, &str)> {
+    match name.trim().rsplit_once(' ') {
+        Some((first_name, last_name)) => Ok((first_name.trim(), last_name)),
+        None => Err(anyhow!(
+            "The name '{}' of the resume needs a first and a last name",
+            name.trim()
+        )),
+    }
+}
+
+fn parse_level(level: &str) -> String {
+    let level = level.trim();
+    let named = ["beginner", "intermediate", "advanced", "expert", "master"]
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(level));
+    match named {
+        Some(index) => (index + 1).to_string(),
+        None => level.to_string(),
+    }
+}
+
+/// JSON Resume allows dates without day or month, which default to the first.
+fn complete_date(date: &str) -> String {
+    match date.trim().matches('-').count() {
+        0 if !date.trim().is_empty() => format!("{}-01-01", date.trim()),
+        1 => format!("{}-01", date.trim()),
+        _ => date.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::employees::usecase::FindEmployees;
+
+    const RESUME: &str = r#"{
+  "basics": {
+    "name": "Ada Augusta Lovelace",
+    "label": "R&D Consultant",
+    "email": "ada@example.com"
+  },
+  "work": [
+    {
+      "name": "Skill Manager",
+      "position": "Development",
+      "summary": "Internal <tool>",
+      "startDate": "2020-01-01",
+      "endDate": "2020-06-30"
+    }
+  ],
+  "skills": [
+    {
+      "name": "Rust",
+      "level": "5"
+    }
+  ]
+}
+"#;
+
+    fn employee(uow: &UnitOfWork) -> Result<Employee> {
+        uow.employees
+            .find_employees(&Default::default())?
+            .items
+            .pop()
+            .ok_or_else(|| anyhow!("No employee imported"))
+    }
+
+    fn export_args(standard: Standard) -> ExportResumeArgs {
+        ExportResumeArgs {
+            employee: "ada".into(),
+            standard,
+            include_secret: false,
+            file: None,
+        }
+    }
+
+    #[test]
+    fn round_trip_test() -> Result<()> {
+        let mut uow = UnitOfWork::in_memory();
+        let report = import_resume(&serde_json::from_str(RESUME)?, &mut uow)?;
+        assert_eq!((report.created, report.rejected.len()), (5, 0));
+
+        let employee = employee(&uow)?;
+        assert_eq!(employee.first_name.0, "Ada Augusta");
+        assert_eq!(
+            export(&export_args(Standard::JsonResume), &employee)?,
+            RESUME
+        );
+
+        let report = import_resume(&serde_json::from_str(RESUME)?, &mut uow)?;
+        assert_eq!((report.created, report.unchanged), (0, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn import_lenient_values_test() -> Result<()> {
+        let mut uow = UnitOfWork::in_memory();
+        let resume = r#"{
+            "basics": {"name": "Alan Turing", "email": "alan@example.com"},
+            "work": [{"name": "Enigma", "startDate": "1939-09"}, {"name": "ACE"}],
+            "skills": [{"name": "Cryptanalysis", "level": "Master"}]
+        }"#;
+
+        let report = import_resume(&serde_json::from_str(resume)?, &mut uow)?;
+
+        assert_eq!(
+            report
+                .rejected
+                .iter()
+                .map(|rejected| rejected.reason.as_str())
+                .collect::<Vec<_>>(),
+            vec!["work entry 2: Missing value for start_date"]
+        );
+        let employee = employee(&uow)?;
+        assert_eq!(employee.skills[0].level.0, 5);
+        assert_eq!(employee.projects[0].start_date.format("%F"), "1939-09-01");
+
+        let resume = r#"{"basics": {"name": "Grace Hopper"}}"#;
+        assert!(import_resume(&serde_json::from_str(resume)?, &mut uow).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn split_name_test() -> Result<()> {
+        assert_eq!(split_name(" Ada  Lovelace ")?, ("Ada", "Lovelace"));
+        assert_eq!(
+            split_name("Ada Augusta Lovelace")?,
+            ("Ada Augusta", "Lovelace")
+        );
+        assert_eq!(
+            split_name(" Ada ").unwrap_err().to_string(),
+            "The name 'Ada' of the resume needs a first and a last name"
+        );
+        assert!(split_name("").is_err());
+
+        let mut uow = UnitOfWork::in_memory();
+        let resume = r#"{"basics": {"name": "Ada", "email": "ada@example.com"}}"#;
+        let error = import_resume(&serde_json::from_str(resume)?, &mut uow).unwrap_err();
+        assert!(error.to_string().contains("needs a first and a last name"));
+        assert_eq!(uow.employees.0.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn europass_test() -> Result<()> {
+        let mut uow = UnitOfWork::in_memory();
+        import_resume(&serde_json::from_str(RESUME)?, &mut uow)?;
+
+        let xml = export(&export_args(Standard::Europass), &employee(&uow)?)?;
+
+        assert!(xml.contains("<FirstName>Ada Augusta</FirstName>"));
+        assert!(xml.contains("<Label>R&amp;D Consultant</Label>"));
+        assert!(xml.contains("<From year=\"2020\" month=\"--01\" day=\"---01\"/>"));
+        assert!(xml.contains("<To year=\"2020\" month=\"--06\" day=\"---30\"/>"));
+        assert!(xml.contains("<Activities>Internal &lt;tool&gt;</Activities>"));
+        assert!(xml.contains("<Description>Rust (5)</Description>"));
+        Ok(())
+    }
+}
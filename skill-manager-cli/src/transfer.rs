@@ -0,0 +1,359 @@
+use anyhow::{anyhow, Context, Result};
+use skill_manager::{
+    employees::usecase::FindEmployees,
+    import::{self, ImportReport, Row},
+    projects::usecase::FindProjects,
+    skills::usecase::FindSkills,
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use structopt::StructOpt;
+
+/// Imports CSV files whose header names the columns, e.g. `first_name,last_name,email`.
+///
+/// Existing entities are updated instead of duplicated, employees are matched by email
+/// address. Rejected rows are reported on stderr.
+#[derive(StructOpt)]
+pub struct ImportArgs {
+    /// One of skills, projects, employees, skill-assignments or project-assignments
+    kind: Kind,
+    /// The CSV file, `-` reads it from stdin
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+    /// Reports what would be imported without saving it
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+/// Exports CSV files in the format expected by `import`.
+#[derive(StructOpt)]
+pub struct ExportArgs {
+    /// One of skills, projects, employees, skill-assignments or project-assignments
+    kind: Kind,
+    /// The CSV file to write, stdout if omitted
+    #[structopt(parse(from_os_str))]
+    file: Option<PathBuf>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Kind {
+    Skills,
+    Projects,
+    Employees,
+    SkillAssignments,
+    ProjectAssignments,
+}
+
+impl FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "skills" => Ok(Kind::Skills),
+            "projects" => Ok(Kind::Projects),
+            "employees" => Ok(Kind::Employees),
+            "skill-assignments" => Ok(Kind::SkillAssignments),
+            "project-assignments" => Ok(Kind::ProjectAssignments),
+            _ => Err(anyhow!(
+                "Unknown kind {}, expected skills, projects, employees, skill-assignments or \
+                 project-assignments",
+                s
+            )),
+        }
+    }
+}
+
+pub fn import(args: &ImportArgs, uow: &mut UnitOfWork) -> Result<ImportReport> {
+    let rows = read_rows(&args.file)?;
+    if args.dry_run {
+        import_rows(args.kind, &rows, &mut uow.detached())
+    } else {
+        import_rows(args.kind, &rows, uow)
+    }
+}
+
+pub(crate) fn import_rows(kind: Kind, rows: &[Row], uow: &mut UnitOfWork) -> Result<ImportReport> {
+    Ok(match kind {
+        Kind::Skills => import::import_skills(rows, &mut *uow.skills)?,
+        Kind::Projects => import::import_projects(rows, &mut *uow.projects)?,
+        Kind::Employees => import::import_employees(rows, &mut *uow.employees)?,
+        Kind::SkillAssignments => {
+            let validated =
+                import::validate_skill_assignments(rows, &*uow.employees, &*uow.skills)?;
+            import::apply_skill_assignments(validated, &mut uow.employees.with(&*uow.skills))
+        }
+        Kind::ProjectAssignments => {
+            let validated =
+                import::validate_project_assignments(rows, &*uow.employees, &*uow.projects)?;
+            import::apply_project_assignments(validated, &mut uow.employees.with(&*uow.projects))
+        }
+    })
+}
+
+fn read_rows(path: &Path) -> Result<Vec<Row>> {
+    let mut contents = String::new();
+    if path.as_os_str() == "-" {
+        io::stdin().read_to_string(&mut contents)?;
+    } else {
+        File::open(path)
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+    }
+    parse_rows(&contents)
+}
+
+fn parse_rows(contents: &str) -> Result<Vec<Row>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::Headers)
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+    let header = reader.headers()?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(Row {
+                line: record.position().map(|p| p.line() as usize).unwrap_or(0),
+                columns: header
+                    .iter()
+                    .map(str::to_string)
+                    .zip(record.iter().map(str::to_string))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+pub fn export(args: &ExportArgs, uow: &UnitOfWork) -> Result<()> {
+    let (header, rows) = export_rows(args.kind, uow)?;
+    let writer: Box<dyn Write> = match &args.file {
+        Some(path) if path.as_os_str() != "-" => Box::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        ),
+        _ => Box::new(io::stdout()),
+    };
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(header)?;
+    for row in rows {
+        writer.write_record(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn export_rows(
+    kind: Kind,
+    uow: &UnitOfWork,
+) -> Result<(&'static [&'static str], Vec<Vec<String>>)> {
+    let employees =
+        || -> Result<_> { Ok(uow.employees.find_employees(&Default::default())?.items) };
+    Ok(match kind {
+        Kind::Skills => (
+            import::SKILL_COLUMNS,
+            uow.skills
+                .find_skills(&Default::default())?
+                .items
+                .into_iter()
+                .map(|skill| vec![skill.label.0])
+                .collect(),
+        ),
+        Kind::Projects => (
+            import::PROJECT_COLUMNS,
+            uow.projects
+                .find_projects(&Default::default())?
+                .items
+                .into_iter()
+                .map(|project| vec![project.label.0, project.description.0])
+                .collect(),
+        ),
+        Kind::Employees => (
+            import::EMPLOYEE_COLUMNS,
+            employees()?
+                .into_iter()
+                .map(|employee| {
+                    vec![
+                        employee.first_name.0,
+                        employee.last_name.0,
+                        employee.title.0,
+                        employee.email.0,
+                        employee.telephone.0,
+                    ]
+                })
+                .collect(),
+        ),
+        Kind::SkillAssignments => (
+            import::SKILL_ASSIGNMENT_COLUMNS,
+            employees()?
+                .into_iter()
+                .flat_map(|employee| {
+                    let email = employee.email.0;
+                    employee.skills.into_iter().map(move |skill| {
+                        vec![
+                            email.clone(),
+                            skill.label.0,
+                            skill.level.to_string(),
+                            skill.secret.to_string(),
+                        ]
+                    })
+                })
+                .collect(),
+        ),
+        Kind::ProjectAssignments => (
+            import::PROJECT_ASSIGNMENT_COLUMNS,
+            employees()?
+                .into_iter()
+                .flat_map(|employee| {
+                    let email = employee.email.0;
+                    employee.projects.into_iter().map(move |project| {
+                        vec![
+                            email.clone(),
+                            project.label.0,
+                            project.contribution.0,
+                            project.start_date.format("%F"),
+                            project.end_date.map(|d| d.format("%F")).unwrap_or_default(),
+                        ]
+                    })
+                })
+                .collect(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn import_csv(kind: Kind, uow: &mut UnitOfWork, csv: &str) -> Result<ImportReport> {
+        import_rows(kind, &parse_rows(csv)?, uow)
+    }
+
+    fn export_csv(kind: Kind, uow: &UnitOfWork) -> Result<String> {
+        let (header, rows) = export_rows(kind, uow)?;
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.write_record(header)?;
+        for row in rows {
+            writer.write_record(row)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+    }
+
+    fn setup() -> Result<UnitOfWork> {
+        let mut uow = UnitOfWork::in_memory();
+        import_csv(Kind::Skills, &mut uow, "label\nRust\nJava\n")?;
+        import_csv(
+            Kind::Projects,
+            &mut uow,
+            "label,description\nSkill Manager,Internal tool\n",
+        )?;
+        import_csv(
+            Kind::Employees,
+            &mut uow,
+            "first_name,last_name,email,title\nAda,Lovelace,ada@example.com,Consultant\n",
+        )?;
+        Ok(uow)
+    }
+
+    #[test]
+    fn upsert_employees_test() -> Result<()> {
+        let mut uow = setup()?;
+
+        let report = import_csv(
+            Kind::Employees,
+            &mut uow,
+            "first_name,last_name,email,telephone\n\
+             Ada,King,ADA@example.com,+49 711\n\
+             Alan,Turing,alan@example.com,\n\
+             Grace,Hopper,,\n\
+             Alan,Turing,alan@example.com,\n",
+        )?;
+
+        assert_eq!(report.created, 1);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 4);
+        assert_eq!(
+            export_csv(Kind::Employees, &uow)?,
+            "first_name,last_name,title,email,telephone\n\
+             Ada,King,Consultant,ADA@example.com,+49 711\n\
+             Alan,Turing,,alan@example.com,\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn import_assignments_test() -> Result<()> {
+        let mut uow = setup()?;
+
+        let report = import_csv(
+            Kind::SkillAssignments,
+            &mut uow,
+            "email,skill,level,secret\n\
+             ada@example.com,rust,4,\n\
+             ada@example.com,Java,2,yes\n\
+             ada@example.com,Go,2,\n\
+             bob@example.com,Rust,2,\n\
+             ada@example.com,Rust,five,\n",
+        )?;
+        assert_eq!(report.created, 2);
+        assert_eq!(
+            report
+                .rejected
+                .iter()
+                .map(|rejected| rejected.reason.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Unknown skill Go",
+                "Unknown employee bob@example.com",
+                "Invalid level five"
+            ]
+        );
+
+        let report = import_csv(
+            Kind::SkillAssignments,
+            &mut uow,
+            "email,skill,level,secret\nada@example.com,Rust,5,false\nada@example.com,Java,2,true\n",
+        )?;
+        assert_eq!((report.updated, report.unchanged), (1, 1));
+
+        let projects = "email,project,contribution,start_date,end_date\n\
+                        ada@example.com,Skill Manager,Development,2020-01-01,\n";
+        let report = import_csv(Kind::ProjectAssignments, &mut uow, projects)?;
+        assert_eq!(report.created, 1);
+        let report = import_csv(Kind::ProjectAssignments, &mut uow, projects)?;
+        assert_eq!(report.unchanged, 1);
+
+        assert_eq!(
+            export_csv(Kind::SkillAssignments, &uow)?,
+            "email,skill,level,secret\nada@example.com,Rust,5,false\nada@example.com,Java,2,true\n"
+        );
+        assert_eq!(export_csv(Kind::ProjectAssignments, &uow)?, projects);
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_test() -> Result<()> {
+        let mut uow = setup()?;
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("skills.csv");
+        std::fs::write(&file, "label\nGo\nRust\n")?;
+
+        let report = import(
+            &ImportArgs {
+                kind: Kind::Skills,
+                file,
+                dry_run: true,
+            },
+            &mut uow,
+        )?;
+
+        assert_eq!((report.created, report.unchanged), (1, 1));
+        assert_eq!(export_csv(Kind::Skills, &uow)?, "label\nJava\nRust\n");
+        Ok(())
+    }
+}
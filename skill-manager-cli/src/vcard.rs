@@ -0,0 +1,451 @@
+use crate::resolve;
+use anyhow::{anyhow, Context, Result};
+use skill_manager::{
+    employees::{
+        usecase::{AddEmployee, AddEmployeeRequest, FindEmployees, GetEmployeeById},
+        EmailAddress, Employee, FirstName, LastName, TelephoneNumber, Title,
+    },
+    import::{ImportReport, RejectedRow},
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+use time::UtcOffset;
+
+/// The maximum length of a content line in octets, longer lines are folded.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Exports employees as vCard 4.0 contacts.
+#[derive(StructOpt)]
+pub struct ExportVcardArgs {
+    /// The employees to export, all employees if omitted
+    employees: Vec<String>,
+    /// The .vcf file to write, stdout if omitted
+    #[structopt(short = "o", long = "file", parse(from_os_str))]
+    pub file: Option<PathBuf>,
+}
+
+/// Adds the contacts of a .vcf file as employees.
+///
+/// Contacts need a first and a last name and an email address or telephone number. Contacts
+/// whose email address already belongs to an employee are rejected as duplicates.
+#[derive(StructOpt)]
+pub struct ImportVcardArgs {
+    /// The .vcf file, `-` reads it from stdin
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+    /// Reports what would be imported without saving it
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+}
+
+pub fn export(
+    args: &ExportVcardArgs,
+    employees: &(impl FindEmployees + GetEmployeeById),
+) -> Result<String> {
+    let selected = if args.employees.is_empty() {
+        employees.find_employees(&Default::default())?.items
+    } else {
+        args.employees
+            .iter()
+            .map(|reference| {
+                let id = resolve::employee(employees, reference)?;
+                employees
+                    .get(id.clone())?
+                    .ok_or_else(|| anyhow!("Employee {} not found", id))
+            })
+            .collect::<Result<_>>()?
+    };
+    Ok(selected.iter().map(vcard).collect())
+}
+
+fn vcard(employee: &Employee) -> String {
+    let mut properties = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:4.0".to_string(),
+        format!(
+            "FN:{}",
+            escape(&format!("{} {}", employee.first_name, employee.last_name))
+        ),
+        format!(
+            "N:{};{};;;",
+            escape(&employee.last_name.0),
+            escape(&employee.first_name.0)
+        ),
+    ];
+    if !employee.title.0.is_empty() {
+        properties.push(format!("TITLE:{}", escape(&employee.title.0)));
+    }
+    if !employee.email.0.is_empty() {
+        properties.push(format!("EMAIL:{}", escape(&employee.email.0)));
+    }
+    if !employee.telephone.0.is_empty() {
+        properties.push(format!("TEL;VALUE=text:{}", escape(&employee.telephone.0)));
+    }
+    properties.push(format!("UID:urn:uuid:{}", employee.id));
+    properties.push(format!(
+        "REV:{}",
+        employee
+            .last_update
+            .to_offset(UtcOffset::UTC)
+            .format("%Y%m%dT%H%M%SZ")
+    ));
+    properties.push("END:VCARD".to_string());
+    properties.iter().map(|property| fold(property)).collect()
+}
+
+/// Escapes a text value, the components of structured values are escaped separately.
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ',' | ';' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Terminates a content line with CRLF, folding it into lines of at most 75 octets that
+/// continue with a space.
+pub(crate) fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+pub fn import(args: &ImportVcardArgs, uow: &mut UnitOfWork) -> Result<ImportReport> {
+    let contents = read_file(&args.file)?;
+    if args.dry_run {
+        import_cards(&contents, &mut *uow.detached().employees)
+    } else {
+        import_cards(&contents, &mut *uow.employees)
+    }
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    if path.as_os_str() == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        Ok(contents)
+    } else {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+    }
+}
+
+fn import_cards(
+    contents: &str,
+    employees: &mut (impl FindEmployees + AddEmployee),
+) -> Result<ImportReport> {
+    let mut emails: HashSet<String> = employees
+        .find_employees(&Default::default())?
+        .items
+        .into_iter()
+        .filter(|employee| !employee.email.0.is_empty())
+        .map(|employee| employee.email.0.to_lowercase())
+        .collect();
+    let mut report = ImportReport::default();
+    for card in parse_cards(contents)? {
+        let result = card.request().and_then(|request| {
+            let email = request.email.0.to_lowercase();
+            if !email.is_empty() && !emails.insert(email) {
+                return Err(format!(
+                    "Duplicate of the employee with email {}",
+                    request.email
+                ));
+            }
+            employees.add(request).map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(_) => report.created += 1,
+            Err(reason) => report.rejected.push(RejectedRow {
+                line: card.line,
+                reason,
+            }),
+        }
+    }
+    Ok(report)
+}
+
+/// The properties of a vCard that map to employees.
+#[derive(Debug, Default, PartialEq)]
+struct Card {
+    /// The line of `BEGIN:VCARD`.
+    line: usize,
+    formatted_name: Option<String>,
+    /// Family name and given name.
+    name: Option<(String, String)>,
+    title: Option<String>,
+    email: Option<String>,
+    telephone: Option<String>,
+}
+
+impl Card {
+    fn request(&self) -> std::result::Result<AddEmployeeRequest, String> {
+        let (last_name, first_name) = match (&self.name, &self.formatted_name) {
+            (Some((last_name, first_name)), _) if !last_name.is_empty() => {
+                (last_name.clone(), first_name.clone())
+            }
+            (_, Some(formatted_name)) => match formatted_name.trim().rsplit_once(' ') {
+                Some((first_name, last_name)) => (last_name.to_string(), first_name.to_string()),
+                None => (formatted_name.trim().to_string(), String::new()),
+            },
+            _ => return Err("Missing name".to_string()),
+        };
+        if last_name.is_empty() {
+            return Err("Missing name".to_string());
+        }
+        if first_name.trim().is_empty() {
+            return Err("Missing first name".to_string());
+        }
+        if self.email.is_none() && self.telephone.is_none() {
+            return Err("Missing email address or telephone number".to_string());
+        }
+        Ok(AddEmployeeRequest {
+            first_name: FirstName(first_name),
+            last_name: LastName(last_name),
+            title: Title(self.title.clone().unwrap_or_default()),
+            email: EmailAddress(self.email.clone().unwrap_or_default()),
+            telephone: TelephoneNumber(self.telephone.clone().unwrap_or_default()),
+        })
+    }
+}
+
+/// Parses the cards of a .vcf file, only the first value of each property is kept.
+fn parse_cards(contents: &str) -> Result<Vec<Card>> {
+    let mut cards = vec![];
+    let mut card: Option<Card> = None;
+    for (line_number, line) in unfold(contents) {
+        let (name, value) = match line.split_once(':') {
+            Some((name, value)) => (name, value),
+            None => return Err(anyhow!("Invalid line {}, expected a property", line_number)),
+        };
+        let mut parameters = name.split(';');
+        let name = parameters.next().unwrap_or_default();
+        // Properties can be grouped like `item1.EMAIL`.
+        let name = name.rsplit('.').next().unwrap_or_default().to_uppercase();
+        if name == "BEGIN" && value.eq_ignore_ascii_case("VCARD") {
+            card = Some(Card {
+                line: line_number,
+                ..Card::default()
+            });
+            continue;
+        }
+        let current = match card.as_mut() {
+            Some(current) => current,
+            None => return Err(anyhow!("Line {} is outside of a vCard", line_number)),
+        };
+        let value = value.trim();
+        match name.as_str() {
+            "END" => cards.extend(card.take()),
+            "FN" => set(&mut current.formatted_name, unescape(value)),
+            "N" => {
+                let mut components = split_components(value).into_iter();
+                let last_name = components.next().unwrap_or_default();
+                let first_name = components.next().unwrap_or_default();
+                current.name.get_or_insert((last_name, first_name));
+            }
+            "TITLE" => set(&mut current.title, unescape(value)),
+            "EMAIL" => set(&mut current.email, unescape(value)),
+            "TEL" => {
+                let value = value.strip_prefix("tel:").unwrap_or(value);
+                set(&mut current.telephone, unescape(value))
+            }
+            _ => {}
+        }
+    }
+    if let Some(card) = card {
+        return Err(anyhow!(
+            "The vCard starting in line {} has no END",
+            card.line
+        ));
+    }
+    Ok(cards)
+}
+
+fn set(property: &mut Option<String>, value: String) {
+    if property.is_none() && !value.is_empty() {
+        *property = Some(value);
+    }
+}
+
+/// Joins folded lines, returning each content line with the number of its first line.
+fn unfold(contents: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        match (
+            line.strip_prefix(|c| c == ' ' || c == '\t'),
+            lines.last_mut(),
+        ) {
+            (Some(continuation), Some((_, previous))) => previous.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push((index + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Splits a structured value at unescaped semicolons and unescapes its components.
+fn split_components(s: &str) -> Vec<String> {
+    let mut components = vec![];
+    let mut component = String::new();
+    let mut escaped = false;
+    for c in s.chars() {
+        match c {
+            _ if escaped => {
+                component.push('\\');
+                component.push(c);
+                escaped = false;
+            }
+            '\\' => escaped = true,
+            ';' => components.push(unescape(&std::mem::take(&mut component))),
+            c => component.push(c),
+        }
+    }
+    components.push(unescape(&component));
+    components
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn import_vcf(uow: &mut UnitOfWork, contents: &str) -> Result<ImportReport> {
+        import_cards(contents, &mut *uow.employees)
+    }
+
+    #[test]
+    fn round_trip_test() -> Result<()> {
+        let mut uow = UnitOfWork::in_memory();
+        let report = import_vcf(
+            &mut uow,
+            "BEGIN:VCARD\r\n\
+             VERSION:4.0\r\n\
+             FN:Ada Lovelace\r\n\
+             N:Lovelace;Ada;Augusta;;\r\n\
+             TITLE:Analyst\\, Poet\r\n\
+             item1.EMAIL;TYPE=work:ada@example.com\r\n\
+             TEL;VALUE=uri:tel:+44-20-7946\r\n\
+             END:VCARD\r\n",
+        )?;
+        assert_eq!(report.created, 1);
+
+        let employees = uow.employees.find_employees(&Default::default())?.items;
+        let vcf = employees.iter().map(vcard).collect::<String>();
+        let mut lines = vcf.split("\r\n");
+        assert_eq!(
+            lines.by_ref().take(7).collect::<Vec<_>>(),
+            vec![
+                "BEGIN:VCARD",
+                "VERSION:4.0",
+                "FN:Ada Lovelace",
+                "N:Lovelace;Ada;;;",
+                "TITLE:Analyst\\, Poet",
+                "EMAIL:ada@example.com",
+                "TEL;VALUE=text:+44-20-7946"
+            ]
+        );
+
+        let mut copy = UnitOfWork::in_memory();
+        import_vcf(&mut copy, &vcf)?;
+        let copied = &copy.employees.find_employees(&Default::default())?.items[0];
+        assert_eq!(
+            (&copied.first_name, &copied.last_name, &copied.title),
+            (
+                &employees[0].first_name,
+                &employees[0].last_name,
+                &employees[0].title
+            )
+        );
+        assert_eq!(
+            (&copied.email, &copied.telephone),
+            (&employees[0].email, &employees[0].telephone)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn duplicates_test() -> Result<()> {
+        let mut uow = UnitOfWork::in_memory();
+        let report = import_vcf(
+            &mut uow,
+            "BEGIN:VCARD\nVERSION:4.0\nFN:Ada Lovelace\nEMAIL:ada@example.com\nEND:VCARD\n\
+             BEGIN:VCARD\nVERSION:4.0\nFN:Ada King\nEMAIL:ADA@example.com\nEND:VCARD\n\
+             BEGIN:VCARD\nVERSION:4.0\nFN:Alan Turing\nEND:VCARD\n\
+             BEGIN:VCARD\nVERSION:4.0\nFN:Grace Hopper\nTEL:+1 202\nEND:VCARD\n\
+             BEGIN:VCARD\nVERSION:4.0\nFN:Ada\nEMAIL:ada@example.org\nEND:VCARD\n\
+             BEGIN:VCARD\nVERSION:4.0\nN:Hopper;;;;\nTEL:+1 203\nEND:VCARD\n",
+        )?;
+
+        assert_eq!(report.created, 2);
+        assert_eq!(
+            report.rejected,
+            vec![
+                RejectedRow {
+                    line: 6,
+                    reason: "Duplicate of the employee with email ADA@example.com".into()
+                },
+                RejectedRow {
+                    line: 11,
+                    reason: "Missing email address or telephone number".into()
+                },
+                RejectedRow {
+                    line: 20,
+                    reason: "Missing first name".into()
+                },
+                RejectedRow {
+                    line: 25,
+                    reason: "Missing first name".into()
+                }
+            ]
+        );
+        assert!(parse_cards("BEGIN:VCARD\nFN:Ada").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fold_test() {
+        let line = format!("TITLE:{}", "ä".repeat(40));
+        let folded = fold(&line);
+
+        assert!(folded
+            .split("\r\n")
+            .all(|line| line.len() <= MAX_LINE_LENGTH));
+        let unfolded = unfold(&folded);
+        assert_eq!(unfolded, vec![(1, line)]);
+    }
+}
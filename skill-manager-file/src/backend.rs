@@ -0,0 +1,217 @@
+//! The storage backends, default locations and config file shared by the CLI and the TUI, so
+//! both work on the same data.
+
+use crate::unit_of_work::UnitOfWork;
+use anyhow::{anyhow, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const APP_DIR: &str = "skill-manager";
+pub const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    File,
+    Memory,
+}
+
+impl Backend {
+    pub fn open(self, data_dir: &Path) -> Result<UnitOfWork> {
+        match self {
+            Backend::File => UnitOfWork::open(data_dir),
+            Backend::Memory => Ok(UnitOfWork::in_memory()),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "file" => Ok(Backend::File),
+            "memory" => Ok(Backend::Memory),
+            _ => Err(anyhow!("Unknown backend {}, expected file or memory", s)),
+        }
+    }
+}
+
+/// Where the data is stored, after combining flags, environment variables, config file and
+/// defaults, in that order of precedence.
+#[derive(Debug, PartialEq)]
+pub struct StorageConfig {
+    pub data_dir: PathBuf,
+    pub backend: Backend,
+    /// The config file given with `--config` or the default one, whether it exists or not.
+    pub config_path: Option<PathBuf>,
+}
+
+/// The storage settings of a config file, every setting is optional.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+struct StorageSettings {
+    data_dir: Option<PathBuf>,
+    backend: Option<Backend>,
+}
+
+impl StorageConfig {
+    /// Reads the config file given with `--config`, or the one in the XDG config directory if
+    /// it exists, and applies the flags on top of it.
+    ///
+    /// Returns the other settings of the config file as `T`, the settings of the binary, which
+    /// decides whether to reject unknown settings.
+    pub fn resolve<T: DeserializeOwned + Default>(
+        config: Option<PathBuf>,
+        data_dir: Option<PathBuf>,
+        backend: Option<Backend>,
+    ) -> Result<(Self, T)> {
+        let config_path = config.clone().or_else(default_config_path);
+        let (storage, settings) = match (config, &config_path) {
+            (Some(path), _) => read_config_file(&path)?,
+            (None, Some(path)) if path.exists() => read_config_file(path)?,
+            _ => (StorageSettings::default(), T::default()),
+        };
+        let config = Self {
+            config_path,
+            ..Self::merge(data_dir, backend, storage, default_data_dir())
+        };
+        Ok((config, settings))
+    }
+
+    fn merge(
+        data_dir: Option<PathBuf>,
+        backend: Option<Backend>,
+        storage: StorageSettings,
+        default_data_dir: PathBuf,
+    ) -> Self {
+        Self {
+            data_dir: data_dir.or(storage.data_dir).unwrap_or(default_data_dir),
+            backend: backend.or(storage.backend).unwrap_or(Backend::File),
+            config_path: None,
+        }
+    }
+
+    pub fn open(&self) -> Result<UnitOfWork> {
+        self.backend.open(&self.data_dir)
+    }
+}
+
+/// Reads a config file, resolving a relative data directory against the config file's
+/// directory.
+fn read_config_file<T: DeserializeOwned>(path: &Path) -> Result<(StorageSettings, T)> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    let (mut storage, settings) = parse_config_file(&contents)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+    if let (Some(data_dir), Some(config_dir)) = (&storage.data_dir, path.parent()) {
+        storage.data_dir = Some(config_dir.join(data_dir));
+    }
+    Ok((storage, settings))
+}
+
+/// Splits a config file into the storage settings and the other settings.
+fn parse_config_file<T: DeserializeOwned>(contents: &str) -> Result<(StorageSettings, T)> {
+    let mut table: toml::value::Table = toml::from_str(contents)?;
+    let storage = StorageSettings {
+        data_dir: table.remove("data_dir").map(|v| v.try_into()).transpose()?,
+        backend: table.remove("backend").map(|v| v.try_into()).transpose()?,
+    };
+    Ok((storage, toml::Value::Table(table).try_into()?))
+}
+
+/// The config file in the XDG config directory.
+pub fn default_config_path() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join(APP_DIR).join(CONFIG_FILE))
+}
+
+/// The data directory in the XDG data directory, or the working directory if there is no
+/// home directory.
+pub fn default_data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// The directory named by an XDG environment variable, or its fallback in the home directory.
+fn xdg_dir(var: &str, home_fallback: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(home_fallback)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Settings of a binary that rejects unknown ones.
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Settings {
+        format: Option<String>,
+    }
+
+    #[test]
+    fn parse_config_file_test() -> Result<()> {
+        let (storage, settings): (_, Settings) = parse_config_file(
+            r#"
+            data_dir = "/var/lib/skill-manager"
+            backend = "memory"
+            format = "table"
+            "#,
+        )?;
+        assert_eq!(
+            storage,
+            StorageSettings {
+                data_dir: Some("/var/lib/skill-manager".into()),
+                backend: Some(Backend::Memory),
+            }
+        );
+        assert_eq!(settings.format.as_deref(), Some("table"));
+
+        assert!(parse_config_file::<Settings>("data_directory = \"/tmp\"").is_err());
+        assert!(parse_config_file::<Settings>("backend = \"sql\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn relative_data_dir_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(CONFIG_FILE);
+        fs::write(&path, "data_dir = \"data\"")?;
+
+        let (storage, _): (_, Settings) = read_config_file(&path)?;
+
+        assert_eq!(storage.data_dir, Some(dir.path().join("data")));
+        Ok(())
+    }
+
+    #[test]
+    fn precedence_test() {
+        let storage = || StorageSettings {
+            data_dir: Some("from-file".into()),
+            backend: Some(Backend::Memory),
+        };
+
+        let config = StorageConfig::merge(None, None, StorageSettings::default(), "default".into());
+        assert_eq!(config.data_dir, PathBuf::from("default"));
+        assert_eq!(config.backend, Backend::File);
+
+        let config = StorageConfig::merge(None, None, storage(), "default".into());
+        assert_eq!(config.data_dir, PathBuf::from("from-file"));
+        assert_eq!(config.backend, Backend::Memory);
+
+        let config = StorageConfig::merge(
+            Some("from-flag".into()),
+            Some(Backend::File),
+            storage(),
+            "default".into(),
+        );
+        assert_eq!(config.data_dir, PathBuf::from("from-flag"));
+        assert_eq!(config.backend, Backend::File);
+    }
+}
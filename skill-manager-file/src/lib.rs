@@ -0,0 +1,4 @@
+pub mod backend;
+pub mod schema;
+mod storage;
+pub mod unit_of_work;
@@ -0,0 +1,303 @@
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display},
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// The collections that are stored in separate files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collection {
+    Skills,
+    Projects,
+    Employees,
+}
+
+impl Collection {
+    pub const ALL: [Collection; 3] = [
+        Collection::Skills,
+        Collection::Projects,
+        Collection::Employees,
+    ];
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Collection::Skills => "skills.json",
+            Collection::Projects => "projects.json",
+            Collection::Employees => "employees.json",
+        }
+    }
+}
+
+impl Display for Collection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file_name())
+    }
+}
+
+type Migration = fn(Collection, Value) -> Result<Value>;
+
+/// `MIGRATIONS[n]` migrates data stored in schema version `n` to version `n + 1`.
+///
+/// Version 0 is the raw serialized collection from before the envelope was introduced.
+const MIGRATIONS: &[Migration] = &[introduce_envelope, add_project_assignment_ids];
+
+pub const CURRENT_SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// The on-disk format of a collection: its data tagged with the schema version.
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    schema_version: u32,
+    data: T,
+}
+
+/// A stored collection that is intact but can't be read by this version.
+///
+/// Unlike a corrupted file it must not be replaced by a backup, that would lose the newer
+/// data on the next commit.
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error(
+        "{collection} has schema version {stored_version}, but only versions up to \
+         {supported_version} are supported. Update skill-manager to read it"
+    )]
+    Unsupported {
+        collection: Collection,
+        stored_version: u64,
+        supported_version: u32,
+    },
+    #[error("Failed to migrate {collection} from schema version {from_version}: {cause}")]
+    Migration {
+        collection: Collection,
+        from_version: u32,
+        cause: String,
+    },
+}
+
+/// A collection that was loaded from an older schema version.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PendingMigration {
+    pub collection: Collection,
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+/// Parses a stored collection, migrating it to the current schema version.
+///
+/// Returns the collection and the schema version it was stored in. A newer schema version
+/// or a failed migration is a [`SchemaError`], other errors mean the file is corrupted.
+pub(crate) fn parse<T: DeserializeOwned>(collection: Collection, s: &str) -> Result<(T, u32)> {
+    let (stored_version, mut data) = match serde_json::from_str(s)? {
+        Value::Object(mut envelope)
+            if envelope.contains_key("schema_version") && envelope.contains_key("data") =>
+        {
+            let version = envelope["schema_version"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("Invalid schema version"))?;
+            (version, envelope.remove("data").unwrap_or_default())
+        }
+        raw => (0, raw),
+    };
+    let stored_version = match u32::try_from(stored_version) {
+        Ok(version) if version <= CURRENT_SCHEMA_VERSION => version,
+        _ => {
+            return Err(SchemaError::Unsupported {
+                collection,
+                stored_version,
+                supported_version: CURRENT_SCHEMA_VERSION,
+            }
+            .into())
+        }
+    };
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(stored_version as usize) {
+        data = migration(collection, data).map_err(|e| SchemaError::Migration {
+            collection,
+            from_version: version as u32,
+            cause: format!("{:#}", e),
+        })?;
+    }
+    Ok((serde_json::from_value(data)?, stored_version))
+}
+
+/// Wraps a collection in an envelope with the current schema version.
+pub(crate) fn envelope<T: Serialize>(data: &T) -> impl Serialize + '_ {
+    Envelope {
+        schema_version: CURRENT_SCHEMA_VERSION,
+        data,
+    }
+}
+
+fn introduce_envelope(_: Collection, data: Value) -> Result<Value> {
+    Ok(data)
+}
+
+fn add_project_assignment_ids(collection: Collection, mut data: Value) -> Result<Value> {
+    if collection != Collection::Employees {
+        return Ok(data);
+    }
+    for (employee_id, employee) in as_object(&mut data)?.iter_mut() {
+        // The migrated file is only rewritten later, so the ids must come out the same on
+        // every load until then.
+        let employee_id = Uuid::parse_str(employee_id)?;
+        let projects = as_object(employee)?
+            .get_mut("projects")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| anyhow!("Employee without projects"))?;
+        for (index, assignment) in projects.iter_mut().enumerate() {
+            let id = Uuid::new_v5(&employee_id, index.to_string().as_bytes());
+            as_object(assignment)?
+                .entry("id")
+                .or_insert_with(|| Value::String(id.to_string()));
+        }
+    }
+    Ok(data)
+}
+
+fn as_object(val: &mut Value) -> Result<&mut Map<String, Value>> {
+    match val {
+        Value::Object(object) => Ok(object),
+        other => Err(anyhow!("Expected an object, found {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{EmployeeId, SkillLevel},
+        projects::ProjectLabel,
+        skills::SkillLabel,
+        version::Version,
+    };
+    use skill_manager_in_memory::{employees::EmployeeDb, projects::ProjectDb, skills::SkillDb};
+
+    const EMPLOYEE_ID: &str = "9f6d3c1e-2b8a-4a8e-9c56-3f1e0d7b2a41";
+
+    fn fixtures(version: u32) -> [&'static str; 3] {
+        match version {
+            0 => [
+                include_str!("../fixtures/v0/skills.json"),
+                include_str!("../fixtures/v0/projects.json"),
+                include_str!("../fixtures/v0/employees.json"),
+            ],
+            1 => [
+                include_str!("../fixtures/v1/skills.json"),
+                include_str!("../fixtures/v1/projects.json"),
+                include_str!("../fixtures/v1/employees.json"),
+            ],
+            2 => [
+                include_str!("../fixtures/v2/skills.json"),
+                include_str!("../fixtures/v2/projects.json"),
+                include_str!("../fixtures/v2/employees.json"),
+            ],
+            _ => panic!("No fixtures for schema version {}", version),
+        }
+    }
+
+    #[test]
+    fn every_schema_version_has_fixtures_test() {
+        for version in 0..=CURRENT_SCHEMA_VERSION {
+            fixtures(version);
+        }
+    }
+
+    #[test]
+    fn parse_fixtures_test() -> Result<()> {
+        for version in 0..=CURRENT_SCHEMA_VERSION {
+            let [skills, projects, employees] = fixtures(version);
+
+            let (skills, stored_version): (SkillDb, _) = parse(Collection::Skills, skills)?;
+            assert_eq!(stored_version, version);
+            let mut labels: Vec<_> = skills.0.values().map(|s| s.label.clone()).collect();
+            labels.sort();
+            assert_eq!(
+                labels,
+                vec![SkillLabel("Java".into()), SkillLabel("Rust".into())]
+            );
+
+            let (projects, _): (ProjectDb, _) = parse(Collection::Projects, projects)?;
+            let project = projects.0.values().next().unwrap();
+            assert_eq!(project.label, ProjectLabel("Skill Manager".into()));
+
+            let (employees, _): (EmployeeDb, _) = parse(Collection::Employees, employees)?;
+            let employee = &employees.0[&EMPLOYEE_ID.parse::<EmployeeId>()?];
+            assert_eq!(employee.skills[0].level, SkillLevel(4));
+            assert_eq!(employee.projects.len(), 2);
+            assert_ne!(employee.projects[0].id, employee.projects[1].id);
+            if version >= 1 {
+                assert_eq!(employee.version, Version(3));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn migrated_project_assignment_ids_are_stable_test() -> Result<()> {
+        for version in 0..2 {
+            let [_, _, employees] = fixtures(version);
+            let (first, _): (EmployeeDb, _) = parse(Collection::Employees, employees)?;
+            let (second, _): (EmployeeDb, _) = parse(Collection::Employees, employees)?;
+            assert_eq!(first.0, second.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_test() -> Result<()> {
+        let [_, _, employees] = fixtures(0);
+        let (employees, _): (EmployeeDb, _) = parse(Collection::Employees, employees)?;
+
+        let stored = serde_json::to_string(&envelope(&employees))?;
+        let (reparsed, stored_version): (EmployeeDb, _) = parse(Collection::Employees, &stored)?;
+
+        assert_eq!(stored_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(reparsed.0, employees.0);
+        Ok(())
+    }
+
+    #[test]
+    fn newer_schema_version_is_rejected_test() {
+        let stored = format!(
+            r#"{{"schema_version": {}, "data": {{}}}}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        let error = parse::<SkillDb>(Collection::Skills, &stored).err().unwrap();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(SchemaError::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn huge_schema_version_is_not_truncated_test() {
+        let stored = format!(
+            r#"{{"schema_version": {}, "data": {{}}}}"#,
+            u64::from(u32::MAX) + 1
+        );
+        let error = parse::<SkillDb>(Collection::Skills, &stored).err().unwrap();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(SchemaError::Unsupported { stored_version, .. })
+                if *stored_version == u64::from(u32::MAX) + 1
+        ));
+    }
+
+    #[test]
+    fn failed_migration_is_a_schema_error_test() {
+        let stored = r#"{"schema_version": 1, "data": {"id": {"projects": 42}}}"#;
+        let error = parse::<EmployeeDb>(Collection::Employees, stored)
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(SchemaError::Migration {
+                from_version: 1,
+                ..
+            })
+        ));
+    }
+}
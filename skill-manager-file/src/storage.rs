@@ -0,0 +1,205 @@
+use crate::schema::SchemaError;
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::{
+    ffi::OsString,
+    fmt::{self, Display},
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Number of previous versions of a file that are kept as `<file>.bak.<n>`.
+const BACKUP_COUNT: usize = 3;
+
+/// A corrupted file that was loaded from one of its backups instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredBackup {
+    pub file_path: PathBuf,
+    pub backup_path: PathBuf,
+}
+
+impl Display for RecoveredBackup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is corrupted, loaded backup {}",
+            self.file_path.display(),
+            self.backup_path.display()
+        )
+    }
+}
+
+/// Loads a file, falling back to the newest parseable backup if the file is corrupted.
+///
+/// A missing file yields `None`. A [`SchemaError`] is returned as is, the file is intact and
+/// the backups are older.
+pub(crate) fn load<T>(
+    file_path: &Path,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<Option<(T, Option<RecoveredBackup>)>> {
+    match fs::read_to_string(file_path) {
+        Ok(file_contents) => match parse(&file_contents) {
+            Ok(val) => Ok(Some((val, None))),
+            Err(e) if e.is::<SchemaError>() => {
+                Err(e.context(format!("Failed to load {}", file_path.display())))
+            }
+            Err(e) => recover_from_backup(file_path, parse)
+                .map(|(val, recovered)| Some((val, Some(recovered))))
+                .with_context(|| format!("Failed to parse {}: {}", file_path.display(), e)),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the value to `<file>.tmp` and syncs it to disk.
+pub(crate) fn write_tmp(file_path: &Path, val: &impl Serialize) -> Result<PathBuf> {
+    let tmp_path = tmp_path(file_path);
+    let tmp_file = File::create(&tmp_path)?;
+    let mut writer = io::BufWriter::new(tmp_file);
+    serde_json::to_writer_pretty(&mut writer, val)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    Ok(tmp_path)
+}
+
+/// Replaces the file with its synced `<file>.tmp`, rotating the old version into the backups.
+///
+/// A crash leaves either the old or the new version behind, never a partially written file.
+pub(crate) fn replace_with_tmp(file_path: &Path) -> Result<()> {
+    rotate_backups(file_path)?;
+    fs::rename(tmp_path(file_path), file_path)?;
+    Ok(())
+}
+
+fn recover_from_backup<T>(
+    file_path: &Path,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<(T, RecoveredBackup)> {
+    for n in 1..=BACKUP_COUNT {
+        let backup_path = backup_path(file_path, n);
+        let backup = match fs::read_to_string(&backup_path) {
+            Ok(backup) => backup,
+            Err(_) => continue,
+        };
+        if let Ok(val) = parse(&backup) {
+            let recovered = RecoveredBackup {
+                file_path: file_path.to_path_buf(),
+                backup_path,
+            };
+            return Ok((val, recovered));
+        }
+    }
+    Err(anyhow!("No usable backup found"))
+}
+
+fn rotate_backups(file_path: &Path) -> Result<()> {
+    if !file_path.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(file_path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(file_path, n + 1))?;
+        }
+    }
+    fs::copy(file_path, backup_path(file_path, 1))?;
+    Ok(())
+}
+
+pub(crate) fn sync_dir(dir: &Path) -> Result<()> {
+    if cfg!(unix) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+pub(crate) fn tmp_path(file_path: &Path) -> PathBuf {
+    sibling_path(file_path, ".tmp")
+}
+
+fn backup_path(file_path: &Path, n: usize) -> PathBuf {
+    sibling_path(file_path, &format!(".bak.{}", n))
+}
+
+fn sibling_path(file_path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = OsString::from(file_path.as_os_str());
+    file_name.push(suffix);
+    PathBuf::from(file_name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    type TestDb = BTreeMap<String, usize>;
+
+    fn save(path: &Path, count: usize) -> Result<()> {
+        let mut db = TestDb::new();
+        db.insert("count".into(), count);
+        write_tmp(path, &db)?;
+        replace_with_tmp(path)
+    }
+
+    fn load_count(path: &Path) -> Result<Option<usize>> {
+        let loaded: Option<(TestDb, _)> = load(path, |s| Ok(serde_json::from_str(s)?))?;
+        Ok(loaded.and_then(|(db, _)| db.get("count").cloned()))
+    }
+
+    #[test]
+    fn persist_and_reload_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("db.json");
+
+        assert_eq!(load_count(&path)?, None);
+        save(&path, 1)?;
+
+        assert_eq!(load_count(&path)?, Some(1));
+        assert!(!tmp_path(&path).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rotates_backups_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("db.json");
+
+        for i in 0..BACKUP_COUNT + 2 {
+            save(&path, i)?;
+        }
+
+        assert_eq!(load_count(&backup_path(&path, 1))?, Some(BACKUP_COUNT));
+        assert!(backup_path(&path, BACKUP_COUNT).exists());
+        assert!(!backup_path(&path, BACKUP_COUNT + 1).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn recovers_from_backup_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("db.json");
+
+        save(&path, 0)?;
+        save(&path, 1)?;
+        fs::write(&path, "{\"count\": ")?;
+
+        assert_eq!(load_count(&path)?, Some(0));
+        let (_, recovered): (TestDb, _) = load(&path, |s| Ok(serde_json::from_str(s)?))?.unwrap();
+        assert_eq!(
+            recovered,
+            Some(RecoveredBackup {
+                file_path: path.clone(),
+                backup_path: backup_path(&path, 1),
+            })
+        );
+
+        fs::remove_file(backup_path(&path, 1))?;
+        assert!(load_count(&path).is_err());
+
+        Ok(())
+    }
+}
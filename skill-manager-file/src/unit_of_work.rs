@@ -0,0 +1,620 @@
+pub use crate::storage::RecoveredBackup;
+use crate::{
+    schema::{self, Collection, PendingMigration, CURRENT_SCHEMA_VERSION},
+    storage,
+};
+use anyhow::{anyhow, Context, Result};
+use fs2::FileExt;
+use serde::{de::DeserializeOwned, Serialize};
+use skill_manager_in_memory::{employees::EmployeeDb, projects::ProjectDb, skills::SkillDb};
+use std::{
+    fs::{self, File, OpenOptions},
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+const LOCK_FILE: &str = ".skill-manager.lock";
+const JOURNAL_FILE: &str = ".skill-manager.journal";
+/// How long `open` waits for another process to release the data directory.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A collection that remembers whether it was borrowed mutably since it was loaded.
+pub struct Tracked<T> {
+    val: T,
+    changed: bool,
+    stored_schema_version: Option<u32>,
+}
+
+impl<T: DeserializeOwned + Default> Tracked<T> {
+    /// Loads a collection, adding it to `recovered` if it was loaded from a backup.
+    fn load(
+        dir: &Path,
+        collection: Collection,
+        recovered: &mut Vec<RecoveredBackup>,
+    ) -> Result<Self> {
+        let loaded = storage::load(&dir.join(collection.file_name()), |s| {
+            schema::parse(collection, s)
+        })?;
+        let (val, stored_schema_version) = match loaded {
+            Some(((val, version), recovered_backup)) => {
+                recovered.extend(recovered_backup);
+                (val, Some(version))
+            }
+            None => (T::default(), None),
+        };
+        Ok(Self {
+            val,
+            changed: false,
+            stored_schema_version,
+        })
+    }
+}
+
+impl<T: Default> Tracked<T> {
+    fn empty() -> Self {
+        Self {
+            val: T::default(),
+            changed: false,
+            stored_schema_version: None,
+        }
+    }
+}
+
+impl<T> Tracked<T> {
+    pub fn is_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn mark_saved(&mut self) {
+        if self.changed {
+            self.changed = false;
+            self.stored_schema_version = Some(CURRENT_SCHEMA_VERSION);
+        }
+    }
+}
+
+impl<T: Clone> Clone for Tracked<T> {
+    fn clone(&self) -> Self {
+        Self {
+            val: self.val.clone(),
+            changed: self.changed,
+            stored_schema_version: self.stored_schema_version,
+        }
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.val
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.changed = true;
+        &mut self.val
+    }
+}
+
+/// The skills, projects and employees of a data directory, loaded together and committed
+/// all-or-nothing.
+///
+/// The data directory is locked from opening until the unit of work is committed or dropped,
+/// so concurrent invocations are serialized instead of overwriting each other's changes.
+/// Opening fails if another process holds the lock for longer than a few seconds, e.g. the
+/// TUI for its whole session.
+/// Dropping a unit of work without committing discards its changes.
+pub struct UnitOfWork {
+    pub skills: Tracked<SkillDb>,
+    pub projects: Tracked<ProjectDb>,
+    pub employees: Tracked<EmployeeDb>,
+    storage: Option<Storage>,
+    recovered_backups: Vec<RecoveredBackup>,
+}
+
+struct Storage {
+    dir: PathBuf,
+    _lock: File,
+}
+
+/// The collections of a unit of work at some point, see `UnitOfWork::snapshot`.
+pub struct Snapshot {
+    skills: Tracked<SkillDb>,
+    projects: Tracked<ProjectDb>,
+    employees: Tracked<EmployeeDb>,
+}
+
+impl UnitOfWork {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        Self::open_with_timeout(dir.into(), LOCK_TIMEOUT)
+    }
+
+    fn open_with_timeout(dir: PathBuf, timeout: Duration) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(dir.join(LOCK_FILE))?;
+        lock_with_timeout(&lock, &dir, timeout)?;
+
+        recover(&dir)?;
+
+        let mut recovered_backups = vec![];
+        Ok(Self {
+            skills: Tracked::load(&dir, Collection::Skills, &mut recovered_backups)?,
+            projects: Tracked::load(&dir, Collection::Projects, &mut recovered_backups)?,
+            employees: Tracked::load(&dir, Collection::Employees, &mut recovered_backups)?,
+            storage: Some(Storage { dir, _lock: lock }),
+            recovered_backups,
+        })
+    }
+
+    /// A unit of work that starts out empty and isn't backed by any files, so committing it
+    /// discards its changes.
+    pub fn in_memory() -> Self {
+        Self {
+            skills: Tracked::empty(),
+            projects: Tracked::empty(),
+            employees: Tracked::empty(),
+            storage: None,
+            recovered_backups: vec![],
+        }
+    }
+
+    /// A copy of the loaded collections that isn't backed by any files, for trying out changes
+    /// without saving them.
+    pub fn detached(&self) -> Self {
+        Self {
+            skills: self.skills.clone(),
+            projects: self.projects.clone(),
+            employees: self.employees.clone(),
+            storage: None,
+            recovered_backups: vec![],
+        }
+    }
+
+    /// The collections as they are now, to go back to them with `restore`.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            skills: self.skills.clone(),
+            projects: self.projects.clone(),
+            employees: self.employees.clone(),
+        }
+    }
+
+    /// Discards the changes made since the snapshot was taken.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.skills = snapshot.skills;
+        self.projects = snapshot.projects;
+        self.employees = snapshot.employees;
+    }
+
+    /// The corrupted files that were loaded from a backup when opening, the caller decides how
+    /// to warn about them.
+    pub fn recovered_backups(&self) -> &[RecoveredBackup] {
+        &self.recovered_backups
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.changed_files().is_empty()
+    }
+
+    /// The collections whose files are stored in an outdated schema version.
+    ///
+    /// They are migrated when loaded, but only rewritten in the current schema version when
+    /// they are changed or explicitly migrated.
+    pub fn pending_migrations(&self) -> Vec<PendingMigration> {
+        let stored_schema_versions = [
+            (Collection::Skills, self.skills.stored_schema_version),
+            (Collection::Projects, self.projects.stored_schema_version),
+            (Collection::Employees, self.employees.stored_schema_version),
+        ];
+        stored_schema_versions
+            .iter()
+            .filter_map(|(collection, version)| match version {
+                Some(version) if *version < CURRENT_SCHEMA_VERSION => Some(PendingMigration {
+                    collection: *collection,
+                    from_version: *version,
+                    to_version: CURRENT_SCHEMA_VERSION,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Marks all collections with pending migrations as changed, so they are rewritten in the
+    /// current schema version on commit.
+    pub fn migrate(&mut self) -> Vec<PendingMigration> {
+        let pending = self.pending_migrations();
+        for migration in &pending {
+            match migration.collection {
+                Collection::Skills => self.skills.changed = true,
+                Collection::Projects => self.projects.changed = true,
+                Collection::Employees => self.employees.changed = true,
+            }
+        }
+        pending
+    }
+
+    /// Replaces the files of all changed collections, or none of them if anything fails.
+    ///
+    /// All changed collections are first written to synced temporary files. Writing the
+    /// journal that lists them is the commit point: if the process crashes after that, the
+    /// next `open` finishes replacing the files, otherwise it discards the temporary files.
+    pub fn commit(mut self) -> Result<()> {
+        self.save()
+    }
+
+    /// Commits the changes like `commit`, but keeps the data directory locked and the
+    /// collections loaded, so they can be changed and saved again.
+    ///
+    /// A unit of work that isn't backed by any files keeps its changes.
+    pub fn save(&mut self) -> Result<()> {
+        let dir = match &self.storage {
+            Some(storage) => &storage.dir,
+            None => return Ok(()),
+        };
+        let changed = self.changed_files();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        if let Err(e) = self
+            .write_tmp_files(dir)
+            .and_then(|_| write_journal(dir, &changed))
+        {
+            discard_tmp_files(dir)?;
+            return Err(e);
+        }
+        apply_journal(dir, &changed)?;
+        self.skills.mark_saved();
+        self.projects.mark_saved();
+        self.employees.mark_saved();
+        Ok(())
+    }
+
+    fn changed_files(&self) -> Vec<String> {
+        let mut changed = vec![];
+        if self.skills.is_changed() {
+            changed.push(Collection::Skills.file_name().to_string());
+        }
+        if self.projects.is_changed() {
+            changed.push(Collection::Projects.file_name().to_string());
+        }
+        if self.employees.is_changed() {
+            changed.push(Collection::Employees.file_name().to_string());
+        }
+        changed
+    }
+
+    fn write_tmp_files(&self, dir: &Path) -> Result<()> {
+        write_tmp_file(dir, Collection::Skills, &self.skills)?;
+        write_tmp_file(dir, Collection::Projects, &self.projects)?;
+        write_tmp_file(dir, Collection::Employees, &self.employees)
+    }
+}
+
+/// Locks the data directory, waiting for another process to release it until the timeout.
+fn lock_with_timeout(lock: &File, dir: &Path, timeout: Duration) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        match lock.try_lock_exclusive() {
+            Ok(()) => return Ok(()),
+            Err(e) if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() => {
+                if start.elapsed() >= timeout {
+                    return Err(anyhow!(
+                        "The data directory {} is locked by another process, e.g. an open \
+                         skill-manager-tui",
+                        dir.display()
+                    ));
+                }
+                thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to lock {}", dir.display())),
+        }
+    }
+}
+
+fn write_tmp_file<T: Serialize>(
+    dir: &Path,
+    collection: Collection,
+    tracked: &Tracked<T>,
+) -> Result<()> {
+    if tracked.is_changed() {
+        let file_path = dir.join(collection.file_name());
+        storage::write_tmp(&file_path, &schema::envelope(&tracked.val))?;
+    }
+    Ok(())
+}
+
+/// Finishes a commit that was interrupted after its journal was written and discards the
+/// leftovers of one that was interrupted before.
+fn recover(dir: &Path) -> Result<()> {
+    let journal_path = dir.join(JOURNAL_FILE);
+    if journal_path.exists() {
+        let journal = fs::read_to_string(&journal_path)?;
+        let changed: Vec<String> = serde_json::from_str(&journal)
+            .with_context(|| format!("Failed to parse {}", journal_path.display()))?;
+        apply_journal(dir, &changed)?;
+    }
+    discard_tmp_files(dir)
+}
+
+fn write_journal(dir: &Path, changed: &[String]) -> Result<()> {
+    let journal_path = dir.join(JOURNAL_FILE);
+    let tmp_path = storage::write_tmp(&journal_path, &changed)?;
+    fs::rename(tmp_path, journal_path)?;
+    storage::sync_dir(dir)
+}
+
+fn apply_journal(dir: &Path, changed: &[String]) -> Result<()> {
+    for file_name in changed {
+        let file_path = dir.join(file_name);
+        if storage::tmp_path(&file_path).exists() {
+            storage::replace_with_tmp(&file_path)?;
+        }
+    }
+    storage::sync_dir(dir)?;
+    fs::remove_file(dir.join(JOURNAL_FILE))?;
+    storage::sync_dir(dir)
+}
+
+fn discard_tmp_files(dir: &Path) -> Result<()> {
+    let file_names = Collection::ALL
+        .iter()
+        .map(|collection| collection.file_name())
+        .chain(Some(JOURNAL_FILE));
+    for file_name in file_names {
+        let tmp_path = storage::tmp_path(&dir.join(file_name));
+        if tmp_path.exists() {
+            fs::remove_file(tmp_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        projects::{usecase::AddProject, ProjectDescription, ProjectLabel},
+        skills::{
+            usecase::{AddSkill, FindSkills},
+            SkillLabel,
+        },
+    };
+
+    fn add_skill_and_project(uow: &mut UnitOfWork) -> Result<()> {
+        uow.skills.add(SkillLabel("Rust".into()))?;
+        uow.projects.add(
+            ProjectLabel("Project".into()),
+            ProjectDescription("".into()),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn commit_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        assert!(!uow.has_changes());
+        add_skill_and_project(&mut uow)?;
+        assert!(uow.has_changes());
+        uow.commit()?;
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.skills.0.len(), 1);
+        assert_eq!(uow.projects.0.len(), 1);
+        assert!(!dir.path().join(Collection::Employees.file_name()).exists());
+        assert!(!dir.path().join(JOURNAL_FILE).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn locked_data_directory_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let uow = UnitOfWork::open(dir.path())?;
+        let timeout = Duration::from_millis(100);
+        let error = UnitOfWork::open_with_timeout(dir.path().into(), timeout)
+            .err()
+            .expect("Opened a locked data directory");
+        assert!(error.to_string().contains("locked by another process"));
+        drop(uow);
+
+        UnitOfWork::open_with_timeout(dir.path().into(), timeout)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn save_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        add_skill_and_project(&mut uow)?;
+        uow.save()?;
+        assert!(!uow.has_changes());
+        uow.skills.add(SkillLabel("Go".into()))?;
+        assert_eq!(uow.changed_files(), vec![Collection::Skills.file_name()]);
+        uow.save()?;
+        drop(uow);
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.skills.0.len(), 2);
+        assert_eq!(uow.projects.0.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn restore_snapshot_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        let snapshot = uow.snapshot();
+        add_skill_and_project(&mut uow)?;
+        uow.restore(snapshot);
+        assert!(!uow.has_changes());
+        assert_eq!(uow.skills.0.len(), 0);
+
+        uow.skills.add(SkillLabel("Rust".into()))?;
+        let snapshot = uow.snapshot();
+        add_skill_and_project(&mut uow)?;
+        uow.restore(snapshot);
+        assert_eq!(uow.changed_files(), vec![Collection::Skills.file_name()]);
+        assert_eq!(uow.skills.0.len(), 1);
+        assert_eq!(uow.projects.0.len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_only_access_is_not_a_change_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.skills.find_skills(&Default::default())?.total, 0);
+        assert!(!uow.has_changes());
+
+        Ok(())
+    }
+
+    #[test]
+    fn dropped_changes_are_discarded_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        add_skill_and_project(&mut uow)?;
+        uow.write_tmp_files(dir.path())?;
+        drop(uow);
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert!(uow.skills.0.is_empty());
+        assert!(uow.projects.0.is_empty());
+        assert!(!storage::tmp_path(&dir.path().join(Collection::Skills.file_name())).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn detached_changes_are_not_saved_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        uow.skills.add(SkillLabel("Rust".into()))?;
+        let mut detached = uow.detached();
+        detached.skills.add(SkillLabel("Go".into()))?;
+        detached.commit()?;
+        assert_eq!(uow.skills.0.len(), 1);
+        uow.commit()?;
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.skills.0.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interrupted_commit_is_rolled_forward_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        add_skill_and_project(&mut uow)?;
+        uow.write_tmp_files(dir.path())?;
+        write_journal(dir.path(), &uow.changed_files())?;
+        drop(uow);
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.skills.0.len(), 1);
+        assert_eq!(uow.projects.0.len(), 1);
+        assert!(!dir.path().join(JOURNAL_FILE).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn newer_schema_version_is_not_replaced_by_backup_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let skills_path = dir.path().join(Collection::Skills.file_name());
+        let backup_path = dir
+            .path()
+            .join(format!("{}.bak.1", Collection::Skills.file_name()));
+        let newer = format!(
+            r#"{{"schema_version": {}, "data": {{}}}}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        let backup = include_str!("../fixtures/v2/skills.json");
+        fs::write(&skills_path, &newer)?;
+        fs::write(&backup_path, backup)?;
+
+        let error = UnitOfWork::open(dir.path())
+            .err()
+            .expect("Newer schema was loaded");
+        assert!(error.is::<schema::SchemaError>());
+        assert_eq!(fs::read_to_string(&skills_path)?, newer);
+        assert_eq!(fs::read_to_string(&backup_path)?, backup);
+
+        Ok(())
+    }
+
+    #[test]
+    fn recovered_backups_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        add_skill_and_project(&mut uow)?;
+        uow.commit()?;
+        let mut uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.recovered_backups(), &[]);
+        uow.skills.add(SkillLabel("Go".into()))?;
+        uow.commit()?;
+
+        let skills_path = dir.path().join(Collection::Skills.file_name());
+        fs::write(&skills_path, "{")?;
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.skills.0.len(), 1);
+        assert_eq!(
+            uow.recovered_backups(),
+            &[RecoveredBackup {
+                backup_path: dir.path().join("skills.json.bak.1"),
+                file_path: skills_path,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn migrate_test() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let skills_path = dir.path().join(Collection::Skills.file_name());
+        fs::write(&skills_path, include_str!("../fixtures/v0/skills.json"))?;
+
+        let uow = UnitOfWork::open(dir.path())?;
+        let pending = vec![PendingMigration {
+            collection: Collection::Skills,
+            from_version: 0,
+            to_version: CURRENT_SCHEMA_VERSION,
+        }];
+        assert_eq!(uow.pending_migrations(), pending);
+        uow.commit()?;
+
+        let mut uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.migrate(), pending);
+        uow.commit()?;
+
+        let uow = UnitOfWork::open(dir.path())?;
+        assert_eq!(uow.pending_migrations(), vec![]);
+        assert_eq!(uow.skills.0.len(), 2);
+
+        Ok(())
+    }
+}
@@ -1,18 +1,27 @@
-use crate::{projects::ProjectDb, skills::SkillDb};
+use crate::{projects::ProjectDb, query::sort, skills::SkillDb};
 use serde::{Deserialize, Serialize};
-use skill_manager::employees::{
-    usecase::{
-        AddEmployee, AddEmployeeRequest, AssignProjectToEmployeeError, AssignSkillToEmployeeError,
-        CreateProjectAssignment, DeleteEmployeeById, EmployeeNotFoundError, FindEmployees,
-        GetEmployeeById, ProjectAssignmentRequest, ProjectNotFoundError,
-        SetSkillKnowledgeOfEmployee,
+use skill_manager::{
+    employees::{
+        usecase::{
+            AddEmployee, AddEmployeeRequest, AssignProjectToEmployeeError,
+            AssignSkillToEmployeeError, CreateProjectAssignment, DeleteEmployeeById,
+            DeleteProjectAssignment, DeleteProjectAssignmentError, DeleteSkillKnowledgeError,
+            DeleteSkillKnowledgeOfEmployee, EmployeeNotFoundError, EmployeeQuery, EmployeeSortKey,
+            FindEmployees, GetEmployeeById, ProjectAssignmentNotFoundError,
+            ProjectAssignmentRequest, ProjectNotFoundError, SetSkillKnowledgeOfEmployee,
+            UpdateEmployee, UpdateEmployeeError, UpdateEmployeeRequest,
+        },
+        Employee, EmployeeId, ProjectAssignment, ProjectAssignmentId, SkillAssignment,
     },
-    Employee, EmployeeId, ProjectAssignment, SkillAssignment,
+    projects::{Project, ProjectLabel},
+    query::Page,
+    skills::{usecase::SkillNotFoundError, Skill, SkillId, SkillLabel},
+    version::Version,
 };
 use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Default, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct EmployeeDb(pub HashMap<EmployeeId, Employee>);
 
 impl AddEmployee for EmployeeDb {
@@ -35,6 +44,7 @@ impl AddEmployee for EmployeeDb {
             skills: vec![],
             projects: vec![],
             last_update: time::OffsetDateTime::now_utc(),
+            version: Version::default(),
         };
         self.0.insert(id, employee.clone());
         Ok(employee)
@@ -42,12 +52,39 @@ impl AddEmployee for EmployeeDb {
 }
 
 impl DeleteEmployeeById for EmployeeDb {
-    fn delete(&mut self, employee_id: EmployeeId) -> skill_manager::Result<()> {
+    fn delete(
+        &mut self,
+        employee_id: EmployeeId,
+        expected_version: Option<Version>,
+    ) -> skill_manager::Result<()> {
+        match self.0.get(&employee_id) {
+            Some(employee) => employee.version.check(expected_version)?,
+            // An expected version means the employee was read before, so it has to exist.
+            None if expected_version.is_some() => return Err(EmployeeNotFoundError.into()),
+            None => {}
+        }
         let _ = self.0.remove(&employee_id);
         Ok(())
     }
 }
 
+impl UpdateEmployee for EmployeeDb {
+    fn update(&mut self, request: UpdateEmployeeRequest) -> Result<Employee, UpdateEmployeeError> {
+        let employee = self
+            .0
+            .get_mut(&request.employee_id)
+            .ok_or(EmployeeNotFoundError)?;
+        employee.version.check(request.expected_version)?;
+        employee.first_name = request.first_name;
+        employee.last_name = request.last_name;
+        employee.title = request.title;
+        employee.email = request.email;
+        employee.telephone = request.telephone;
+        touch(employee);
+        Ok(employee.clone())
+    }
+}
+
 impl GetEmployeeById for EmployeeDb {
     fn get(&self, employee_id: EmployeeId) -> skill_manager::Result<Option<Employee>> {
         Ok(self.0.get(&employee_id).cloned())
@@ -55,8 +92,42 @@ impl GetEmployeeById for EmployeeDb {
 }
 
 impl FindEmployees for EmployeeDb {
-    fn find_employees(&self) -> skill_manager::Result<Vec<Employee>> {
-        Ok(self.0.values().cloned().collect())
+    fn find_employees(&self, query: &EmployeeQuery) -> skill_manager::Result<Page<Employee>> {
+        let mut employees: Vec<Employee> = self
+            .0
+            .values()
+            .filter(|employee| {
+                query.matches(&[
+                    &employee.first_name.0,
+                    &employee.last_name.0,
+                    &employee.title.0,
+                    &employee.email.0,
+                ])
+            })
+            .cloned()
+            .collect();
+        match query.sort {
+            EmployeeSortKey::Name => sort(&mut employees, query.direction, |employee| {
+                (
+                    employee.last_name.0.to_lowercase(),
+                    employee.first_name.0.to_lowercase(),
+                )
+            }),
+            EmployeeSortKey::LastUpdate => sort(&mut employees, query.direction, |employee| {
+                employee.last_update
+            }),
+            EmployeeSortKey::StartDate => {
+                let start_date =
+                    |employee: &Employee| employee.projects.iter().map(|p| p.start_date).min();
+                employees.sort_by(|a, b| {
+                    query
+                        .direction
+                        .apply_missing_last(&start_date(a), &start_date(b))
+                        .then_with(|| a.cmp(b))
+                });
+            }
+        }
+        Ok(Page::paginate(employees, query.page))
     }
 }
 
@@ -67,6 +138,39 @@ impl EmployeeDb {
             other_db,
         }
     }
+
+    /// Renames the skill assignments of a renamed skill.
+    pub fn rename_skill(&mut self, previous: &SkillLabel, skill: &Skill) {
+        for employee in self.0.values_mut() {
+            let mut changed = false;
+            for assignment in &mut employee.skills {
+                if assignment.label == *previous {
+                    assignment.label = skill.label.clone();
+                    changed = true;
+                }
+            }
+            if changed {
+                touch(employee);
+            }
+        }
+    }
+
+    /// Copies the label and description of an updated project to its assignments.
+    pub fn update_project(&mut self, previous: &ProjectLabel, project: &Project) {
+        for employee in self.0.values_mut() {
+            let mut changed = false;
+            for assignment in &mut employee.projects {
+                if assignment.label == *previous {
+                    assignment.label = project.label.clone();
+                    assignment.description = project.description.clone();
+                    changed = true;
+                }
+            }
+            if changed {
+                touch(employee);
+            }
+        }
+    }
 }
 
 pub struct EmployeeDbWith<'a, Db> {
@@ -74,6 +178,11 @@ pub struct EmployeeDbWith<'a, Db> {
     other_db: &'a Db,
 }
 
+fn touch(employee: &mut Employee) {
+    employee.version = employee.version.next();
+    employee.last_update = time::OffsetDateTime::now_utc();
+}
+
 impl CreateProjectAssignment for EmployeeDbWith<'_, ProjectDb> {
     fn create_project_assignment(
         &mut self,
@@ -84,12 +193,16 @@ impl CreateProjectAssignment for EmployeeDbWith<'_, ProjectDb> {
             .0
             .get_mut(&project_assignment.employee_id)
             .ok_or(EmployeeNotFoundError)?;
+        employee
+            .version
+            .check(project_assignment.expected_version)?;
         let project = self
             .other_db
             .0
             .get(&project_assignment.project_id)
             .ok_or(ProjectNotFoundError)?;
         let project_assignment = ProjectAssignment {
+            id: ProjectAssignmentId(Uuid::new_v4()),
             label: project.label.clone(),
             description: project.description.clone(),
             contribution: project_assignment.contribution,
@@ -97,10 +210,31 @@ impl CreateProjectAssignment for EmployeeDbWith<'_, ProjectDb> {
             end_date: project_assignment.end_date,
         };
         employee.projects.push(project_assignment.clone());
+        touch(employee);
         Ok(project_assignment)
     }
 }
 
+impl DeleteProjectAssignment for EmployeeDb {
+    fn delete_project_assignment(
+        &mut self,
+        employee_id: EmployeeId,
+        assignment_id: ProjectAssignmentId,
+        expected_version: Option<Version>,
+    ) -> Result<(), DeleteProjectAssignmentError> {
+        let employee = self.0.get_mut(&employee_id).ok_or(EmployeeNotFoundError)?;
+        employee.version.check(expected_version)?;
+        let ix = employee
+            .projects
+            .iter()
+            .position(|assignment| assignment.id == assignment_id)
+            .ok_or(ProjectAssignmentNotFoundError)?;
+        employee.projects.remove(ix);
+        touch(employee);
+        Ok(())
+    }
+}
+
 impl SetSkillKnowledgeOfEmployee for EmployeeDbWith<'_, SkillDb> {
     fn set_skill_knowledge_of_employee(
         &mut self,
@@ -111,6 +245,7 @@ impl SetSkillKnowledgeOfEmployee for EmployeeDbWith<'_, SkillDb> {
             .0
             .get_mut(&request.employee_id)
             .ok_or(AssignSkillToEmployeeError::EmployeeNotFound)?;
+        employee.version.check(request.expected_version)?;
 
         let skill = self
             .other_db
@@ -123,12 +258,45 @@ impl SetSkillKnowledgeOfEmployee for EmployeeDbWith<'_, SkillDb> {
             level: request.level,
             secret: request.secret,
         };
-        employee.skills.push(assignment.clone());
+        match employee
+            .skills
+            .iter_mut()
+            .find(|existing| existing.label == assignment.label)
+        {
+            Some(existing) => *existing = assignment.clone(),
+            None => employee.skills.push(assignment.clone()),
+        }
+        touch(employee);
 
         Ok(assignment)
     }
 }
 
+impl DeleteSkillKnowledgeOfEmployee for EmployeeDbWith<'_, SkillDb> {
+    fn delete_skill_knowledge_of_employee(
+        &mut self,
+        employee_id: EmployeeId,
+        skill_id: SkillId,
+        expected_version: Option<Version>,
+    ) -> Result<(), DeleteSkillKnowledgeError> {
+        let employee = self
+            .employee_db
+            .0
+            .get_mut(&employee_id)
+            .ok_or(EmployeeNotFoundError)?;
+        employee.version.check(expected_version)?;
+        let skill = self.other_db.0.get(&skill_id).ok_or(SkillNotFoundError)?;
+        let count = employee.skills.len();
+        employee
+            .skills
+            .retain(|assignment| assignment.label != skill.label);
+        if employee.skills.len() != count {
+            touch(employee);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -137,8 +305,12 @@ mod test {
             usecase::SetSkillKnowledgeRequest, EmailAddress, FirstName, LastName,
             ProjectContribution, SkillLevel, TelephoneNumber, Title,
         },
-        projects::{usecase::AddProject, ProjectDescription, ProjectLabel},
-        skills::{usecase::AddSkill, SkillLabel},
+        projects::{
+            usecase::{AddProject, UpdateProject, UpdateProjectRequest},
+            ProjectDescription,
+        },
+        query::SortDirection,
+        skills::usecase::{AddSkill, UpdateSkill, UpdateSkillRequest},
     };
     use time::Date;
 
@@ -183,9 +355,67 @@ mod test {
             Some(employee.clone())
         );
 
-        employee_db.delete(employee.id.clone())?;
+        employee_db.delete(employee.id.clone(), None)?;
 
         assert_eq!(employee_db.get(employee.id.clone())?, None);
+        employee_db.delete(employee.id.clone(), None)?;
+        assert!(matches!(
+            employee_db.delete(employee.id, Some(employee.version)),
+            Err(skill_manager::Error::EmployeeNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sort_by_start_date_test() -> anyhow::Result<()> {
+        let mut project_db = ProjectDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let project = project_db.add(project_label(), project_description())?;
+        for (name, start_date) in &[
+            ("Early", Some("2014-04-01")),
+            ("None", None),
+            ("Late", Some("2016-01-01")),
+        ] {
+            let employee = employee_db.add(AddEmployeeRequest {
+                last_name: LastName(name.to_string()),
+                ..add_employee_request()
+            })?;
+            if let Some(start_date) = start_date {
+                employee_db.with(&project_db).create_project_assignment(
+                    ProjectAssignmentRequest {
+                        employee_id: employee.id,
+                        project_id: project.id.clone(),
+                        contribution: ProjectContribution("".into()),
+                        start_date: Date::parse(start_date, "%F").unwrap(),
+                        end_date: None,
+                        expected_version: None,
+                    },
+                )?;
+            }
+        }
+        let names = |direction| -> anyhow::Result<Vec<String>> {
+            Ok(employee_db
+                .find_employees(&EmployeeQuery {
+                    sort: EmployeeSortKey::StartDate,
+                    direction,
+                    ..Default::default()
+                })?
+                .items
+                .into_iter()
+                .map(|employee| employee.last_name.0)
+                .collect())
+        };
+
+        assert_eq!(
+            names(SortDirection::Ascending)?,
+            vec!["Early", "Late", "None"]
+        );
+        assert_eq!(
+            names(SortDirection::Descending)?,
+            vec!["Late", "Early", "None"]
+        );
 
         Ok(())
     }
@@ -206,6 +436,7 @@ mod test {
                 skill_id: skill.id.clone(),
                 level: skill_level.clone(),
                 secret: false,
+                expected_version: Some(employee.version),
             })?;
 
         assert_eq!(assignment.level, skill_level);
@@ -222,6 +453,39 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn reassign_skill_to_employee_test() -> anyhow::Result<()> {
+        let mut skill_db = SkillDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let skill = skill_db.add(skill_label())?;
+        let employee = employee_db.add(add_employee_request())?;
+        for &(level, secret) in &[(2, false), (4, true)] {
+            employee_db
+                .with(&skill_db)
+                .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+                    employee_id: employee.id.clone(),
+                    skill_id: skill.id.clone(),
+                    level: SkillLevel(level),
+                    secret,
+                    expected_version: None,
+                })?;
+        }
+
+        let updated = employee_db.get(employee.id)?.unwrap();
+        assert_eq!(
+            updated.skills,
+            vec![SkillAssignment {
+                label: skill.label,
+                level: SkillLevel(4),
+                secret: true
+            }]
+        );
+        assert_eq!(updated.version, employee.version.next().next());
+
+        Ok(())
+    }
+
     #[test]
     fn assign_project_to_employee_test() -> anyhow::Result<()> {
         let mut project_db = ProjectDb::default();
@@ -245,6 +509,7 @@ mod test {
                     contribution: ProjectContribution("contribution".into()),
                     start_date: Date::parse("2014-04-01", "%F").unwrap(),
                     end_date: None,
+                    expected_version: None,
                 })?;
 
         assert_eq!(
@@ -254,4 +519,218 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn delete_skill_knowledge_test() -> anyhow::Result<()> {
+        let mut skill_db = SkillDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let skill = skill_db.add(skill_label())?;
+        let employee = employee_db.add(add_employee_request())?;
+        let assigned = employee_db
+            .with(&skill_db)
+            .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+                employee_id: employee.id.clone(),
+                skill_id: skill.id.clone(),
+                level: SkillLevel(3),
+                secret: false,
+                expected_version: None,
+            })?;
+        let version = employee_db.get(employee.id.clone())?.unwrap().version;
+        assert_eq!(assigned.label, skill.label);
+
+        let result = employee_db
+            .with(&skill_db)
+            .delete_skill_knowledge_of_employee(
+                employee.id.clone(),
+                skill.id.clone(),
+                Some(employee.version),
+            );
+        assert!(matches!(
+            result,
+            Err(DeleteSkillKnowledgeError::VersionConflict(_))
+        ));
+
+        employee_db
+            .with(&skill_db)
+            .delete_skill_knowledge_of_employee(
+                employee.id.clone(),
+                skill.id.clone(),
+                Some(version),
+            )?;
+
+        let updated = employee_db.get(employee.id.clone())?.unwrap();
+        assert_eq!(updated.skills, vec![]);
+        assert_eq!(updated.version, version.next());
+
+        let result = employee_db
+            .with(&skill_db)
+            .delete_skill_knowledge_of_employee(EmployeeId(Uuid::new_v4()), skill.id.clone(), None);
+        assert!(matches!(
+            result,
+            Err(DeleteSkillKnowledgeError::EmployeeNotFound(_))
+        ));
+        let result = employee_db
+            .with(&skill_db)
+            .delete_skill_knowledge_of_employee(employee.id, SkillId(Uuid::new_v4()), None);
+        assert!(matches!(
+            result,
+            Err(DeleteSkillKnowledgeError::SkillNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn delete_project_assignment_test() -> anyhow::Result<()> {
+        let mut project_db = ProjectDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let project = project_db.add(project_label(), project_description())?;
+        let employee = employee_db.add(add_employee_request())?;
+        let assignment =
+            employee_db
+                .with(&project_db)
+                .create_project_assignment(ProjectAssignmentRequest {
+                    employee_id: employee.id.clone(),
+                    project_id: project.id.clone(),
+                    contribution: ProjectContribution("contribution".into()),
+                    start_date: Date::parse("2014-04-01", "%F").unwrap(),
+                    end_date: None,
+                    expected_version: None,
+                })?;
+
+        employee_db.delete_project_assignment(employee.id.clone(), assignment.id.clone(), None)?;
+
+        assert_eq!(
+            employee_db.get(employee.id.clone())?.unwrap().projects,
+            vec![]
+        );
+
+        let result = employee_db.delete_project_assignment(employee.id, assignment.id, None);
+        assert!(matches!(
+            result,
+            Err(DeleteProjectAssignmentError::AssignmentNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rename_skill_test() -> anyhow::Result<()> {
+        let mut skill_db = SkillDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let skill = skill_db.add(skill_label())?;
+        let employee = employee_db.add(add_employee_request())?;
+        employee_db
+            .with(&skill_db)
+            .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+                employee_id: employee.id.clone(),
+                skill_id: skill.id.clone(),
+                level: SkillLevel(4),
+                secret: false,
+                expected_version: None,
+            })?;
+        let before = employee_db.get(employee.id.clone())?.unwrap();
+
+        let renamed = skill_db.update(UpdateSkillRequest {
+            skill_id: skill.id.clone(),
+            label: SkillLabel("renamed skill".into()),
+            expected_version: Some(skill.version),
+        })?;
+        employee_db.rename_skill(&skill.label, &renamed);
+
+        let after = employee_db.get(employee.id.clone())?.unwrap();
+        assert_eq!(after.skills[0].label, renamed.label);
+        assert_eq!(after.skills[0].level, SkillLevel(4));
+        assert_eq!(after.version, before.version.next());
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_project_test() -> anyhow::Result<()> {
+        let mut project_db = ProjectDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let project = project_db.add(project_label(), project_description())?;
+        let employee = employee_db.add(add_employee_request())?;
+        let untouched = employee_db.add(add_employee_request())?;
+        employee_db
+            .with(&project_db)
+            .create_project_assignment(ProjectAssignmentRequest {
+                employee_id: employee.id.clone(),
+                project_id: project.id.clone(),
+                contribution: ProjectContribution("contribution".into()),
+                start_date: Date::parse("2014-04-01", "%F").unwrap(),
+                end_date: None,
+                expected_version: None,
+            })?;
+
+        let updated = project_db.update(UpdateProjectRequest {
+            project_id: project.id.clone(),
+            label: ProjectLabel("renamed project".into()),
+            description: ProjectDescription("description".into()),
+            expected_version: None,
+        })?;
+        employee_db.update_project(&project.label, &updated);
+
+        let assignment = &employee_db.get(employee.id.clone())?.unwrap().projects[0];
+        assert_eq!(assignment.label, updated.label);
+        assert_eq!(assignment.description, updated.description);
+        assert_eq!(
+            employee_db.get(untouched.id.clone())?,
+            Some(untouched.clone())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn employee_version_conflict_test() -> anyhow::Result<()> {
+        let mut skill_db = SkillDb::default();
+        let mut employee_db = EmployeeDb::default();
+
+        let skill = skill_db.add(skill_label())?;
+        let employee = employee_db.add(add_employee_request())?;
+        let stale_version = employee.version;
+
+        employee_db
+            .with(&skill_db)
+            .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+                employee_id: employee.id.clone(),
+                skill_id: skill.id.clone(),
+                level: SkillLevel(3),
+                secret: false,
+                expected_version: Some(stale_version),
+            })?;
+
+        let updated = employee_db.get(employee.id.clone())?.unwrap();
+        assert_eq!(updated.version, stale_version.next());
+
+        let result =
+            employee_db
+                .with(&skill_db)
+                .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+                    employee_id: employee.id.clone(),
+                    skill_id: skill.id.clone(),
+                    level: SkillLevel(5),
+                    secret: false,
+                    expected_version: Some(stale_version),
+                });
+        assert!(matches!(
+            result,
+            Err(AssignSkillToEmployeeError::VersionConflict(_))
+        ));
+
+        let result = employee_db.delete(employee.id.clone(), Some(stale_version));
+        assert!(matches!(
+            result,
+            Err(skill_manager::Error::VersionConflict(_))
+        ));
+        assert_eq!(employee_db.get(employee.id.clone())?, Some(updated));
+
+        Ok(())
+    }
 }
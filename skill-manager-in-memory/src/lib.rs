@@ -1,3 +1,4 @@
 pub mod employees;
 pub mod projects;
+mod query;
 pub mod skills;
@@ -1,12 +1,21 @@
+use crate::query::sort;
 use serde::{Deserialize, Serialize};
-use skill_manager::projects::{
-    usecase::{AddProject, DeleteProject, FindProjects, GetProject},
-    Project, ProjectDescription, ProjectId, ProjectLabel,
+use skill_manager::{
+    employees::usecase::ProjectNotFoundError,
+    projects::{
+        usecase::{
+            AddProject, DeleteProject, FindProjects, GetProject, ProjectQuery, ProjectSortKey,
+            UpdateProject, UpdateProjectError, UpdateProjectRequest,
+        },
+        Project, ProjectDescription, ProjectId, ProjectLabel,
+    },
+    query::Page,
+    version::Version,
 };
 use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 pub struct ProjectDb(pub HashMap<ProjectId, Project>);
 
 impl AddProject for ProjectDb {
@@ -20,14 +29,39 @@ impl AddProject for ProjectDb {
             id: id.clone(),
             label,
             description,
+            version: Version::default(),
         };
         self.0.insert(id, project.clone());
         Ok(project)
     }
 }
 
+impl UpdateProject for ProjectDb {
+    fn update(&mut self, request: UpdateProjectRequest) -> Result<Project, UpdateProjectError> {
+        let project = self
+            .0
+            .get_mut(&request.project_id)
+            .ok_or(ProjectNotFoundError)?;
+        project.version.check(request.expected_version)?;
+        project.label = request.label;
+        project.description = request.description;
+        project.version = project.version.next();
+        Ok(project.clone())
+    }
+}
+
 impl DeleteProject for ProjectDb {
-    fn delete(&mut self, project_id: ProjectId) -> skill_manager::Result<()> {
+    fn delete(
+        &mut self,
+        project_id: ProjectId,
+        expected_version: Option<Version>,
+    ) -> skill_manager::Result<()> {
+        match self.0.get(&project_id) {
+            Some(project) => project.version.check(expected_version)?,
+            // An expected version means the project was read before, so it has to exist.
+            None if expected_version.is_some() => return Err(ProjectNotFoundError.into()),
+            None => {}
+        }
         let _ = self.0.remove(&project_id);
         Ok(())
     }
@@ -40,8 +74,19 @@ impl GetProject for ProjectDb {
 }
 
 impl FindProjects for ProjectDb {
-    fn find_projects(&self) -> skill_manager::Result<Vec<Project>> {
-        Ok(self.0.values().cloned().collect())
+    fn find_projects(&self, query: &ProjectQuery) -> skill_manager::Result<Page<Project>> {
+        let mut projects: Vec<Project> = self
+            .0
+            .values()
+            .filter(|project| query.matches(&[&project.label.0, &project.description.0]))
+            .cloned()
+            .collect();
+        match query.sort {
+            ProjectSortKey::Label => sort(&mut projects, query.direction, |project| {
+                project.label.0.to_lowercase()
+            }),
+        }
+        Ok(Page::paginate(projects, query.page))
     }
 }
 
@@ -57,8 +102,47 @@ mod test {
         let added = db.add(project.clone(), ProjectDescription("".into()))?;
         assert_eq!(project, added.label);
         assert_eq!(db.get(added.id.clone())?, Some(added.clone()));
-        db.delete(added.id.clone())?;
-        assert_eq!(db.get(added.id)?, None);
+        db.delete(added.id.clone(), None)?;
+        assert_eq!(db.get(added.id.clone())?, None);
+        db.delete(added.id.clone(), None)?;
+        assert!(matches!(
+            db.delete(added.id, Some(added.version)),
+            Err(skill_manager::Error::ProjectNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_project_test() -> anyhow::Result<()> {
+        let mut db = ProjectDb::default();
+        let added = db.add(
+            ProjectLabel("Example project".into()),
+            ProjectDescription("".into()),
+        )?;
+
+        let updated = db.update(UpdateProjectRequest {
+            project_id: added.id.clone(),
+            label: ProjectLabel("Renamed project".into()),
+            description: ProjectDescription("An example".into()),
+            expected_version: Some(added.version),
+        })?;
+
+        assert_eq!(updated.label, ProjectLabel("Renamed project".into()));
+        assert_eq!(updated.description, ProjectDescription("An example".into()));
+        assert_eq!(updated.version, added.version.next());
+        assert_eq!(db.get(added.id.clone())?, Some(updated));
+
+        let result = db.update(UpdateProjectRequest {
+            project_id: added.id,
+            label: ProjectLabel("Stale project".into()),
+            description: ProjectDescription("".into()),
+            expected_version: Some(added.version),
+        });
+        assert!(matches!(
+            result,
+            Err(UpdateProjectError::VersionConflict(_))
+        ));
 
         Ok(())
     }
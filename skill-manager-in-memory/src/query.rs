@@ -0,0 +1,11 @@
+use skill_manager::query::SortDirection;
+
+/// Sorts by the key in the given direction, breaking ties by the entities' own ordering, which
+/// starts with their id, so that the order doesn't depend on the iteration order of the map.
+pub(crate) fn sort<T: Ord, K: Ord>(
+    items: &mut [T],
+    direction: SortDirection,
+    key: impl Fn(&T) -> K,
+) {
+    items.sort_by(|a, b| direction.apply(key(a).cmp(&key(b))).then_with(|| a.cmp(b)));
+}
@@ -1,7 +1,15 @@
+use crate::query::sort;
 use serde::{Deserialize, Serialize};
-use skill_manager::skills::{
-    usecase::{AddSkill, DeleteSkillById, FindSkills, GetSkillById},
-    Skill, SkillId, SkillLabel,
+use skill_manager::{
+    query::Page,
+    skills::{
+        usecase::{
+            AddSkill, DeleteSkillById, FindSkills, GetSkillById, SkillNotFoundError, SkillQuery,
+            SkillSortKey, UpdateSkill, UpdateSkillError, UpdateSkillRequest,
+        },
+        Skill, SkillId, SkillLabel,
+    },
+    version::Version,
 };
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -16,8 +24,19 @@ impl GetSkillById for SkillDb {
 }
 
 impl FindSkills for SkillDb {
-    fn find_skills(&self) -> skill_manager::Result<Vec<Skill>> {
-        Ok(self.0.values().cloned().collect())
+    fn find_skills(&self, query: &SkillQuery) -> skill_manager::Result<Page<Skill>> {
+        let mut skills: Vec<Skill> = self
+            .0
+            .values()
+            .filter(|skill| query.matches(&[&skill.label.0]))
+            .cloned()
+            .collect();
+        match query.sort {
+            SkillSortKey::Label => sort(&mut skills, query.direction, |skill| {
+                skill.label.0.to_lowercase()
+            }),
+        }
+        Ok(Page::paginate(skills, query.page))
     }
 }
 
@@ -27,14 +46,38 @@ impl AddSkill for SkillDb {
         let skill = Skill {
             id: id.clone(),
             label,
+            version: Version::default(),
         };
         let _ = self.0.insert(id, skill.clone());
         Ok(skill)
     }
 }
 
+impl UpdateSkill for SkillDb {
+    fn update(&mut self, request: UpdateSkillRequest) -> Result<Skill, UpdateSkillError> {
+        let skill = self
+            .0
+            .get_mut(&request.skill_id)
+            .ok_or(SkillNotFoundError)?;
+        skill.version.check(request.expected_version)?;
+        skill.label = request.label;
+        skill.version = skill.version.next();
+        Ok(skill.clone())
+    }
+}
+
 impl DeleteSkillById for SkillDb {
-    fn delete(&mut self, skill_id: SkillId) -> skill_manager::Result<()> {
+    fn delete(
+        &mut self,
+        skill_id: SkillId,
+        expected_version: Option<Version>,
+    ) -> skill_manager::Result<()> {
+        match self.0.get(&skill_id) {
+            Some(skill) => skill.version.check(expected_version)?,
+            // An expected version means the skill was read before, so it has to exist.
+            None if expected_version.is_some() => return Err(SkillNotFoundError.into()),
+            None => {}
+        }
         let _ = self.0.remove(&skill_id);
         Ok(())
     }
@@ -43,26 +86,124 @@ impl DeleteSkillById for SkillDb {
 #[cfg(test)]
 mod test {
     use super::*;
-    use skill_manager::skills::SkillLabel;
+    use skill_manager::{
+        query::{PageRequest, Query, SortDirection},
+        skills::SkillLabel,
+    };
 
     #[test]
     fn skill_api_test() -> skill_manager::Result<()> {
         let mut db = SkillDb::default();
         let skill = SkillLabel("Example".into());
 
-        assert_eq!(db.find_skills()?, vec![]);
+        assert_eq!(db.find_skills(&SkillQuery::default())?.items, vec![]);
 
         let added_skill = db.add(skill.clone())?;
 
         assert_eq!(added_skill.label, skill);
         assert_eq!(db.get(added_skill.id.clone())?.unwrap(), added_skill);
-        assert_eq!(db.find_skills()?, vec![added_skill.clone()]);
+        assert_eq!(
+            db.find_skills(&SkillQuery::default())?.items,
+            vec![added_skill.clone()]
+        );
 
-        db.delete(added_skill.id.clone())?;
+        db.delete(added_skill.id.clone(), None)?;
 
-        assert_eq!(db.find_skills()?, vec![]);
+        assert_eq!(db.find_skills(&SkillQuery::default())?.items, vec![]);
         assert_eq!(db.get(added_skill.id)?, None);
 
         Ok(())
     }
+
+    #[test]
+    fn delete_skill_version_conflict_test() -> skill_manager::Result<()> {
+        let mut db = SkillDb::default();
+        let added_skill = db.add(SkillLabel("Example".into()))?;
+
+        let result = db.delete(added_skill.id.clone(), Some(added_skill.version.next()));
+
+        assert!(matches!(
+            result,
+            Err(skill_manager::Error::VersionConflict(_))
+        ));
+        assert_eq!(db.get(added_skill.id.clone())?, Some(added_skill.clone()));
+
+        db.delete(added_skill.id.clone(), Some(added_skill.version))?;
+
+        assert_eq!(db.get(added_skill.id.clone())?, None);
+        assert!(matches!(
+            db.delete(added_skill.id.clone(), Some(added_skill.version)),
+            Err(skill_manager::Error::SkillNotFound(_))
+        ));
+        db.delete(added_skill.id, None)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn update_skill_test() -> anyhow::Result<()> {
+        let mut db = SkillDb::default();
+        let added_skill = db.add(SkillLabel("Rust".into()))?;
+
+        let updated_skill = db.update(UpdateSkillRequest {
+            skill_id: added_skill.id.clone(),
+            label: SkillLabel("Rust 2018".into()),
+            expected_version: Some(added_skill.version),
+        })?;
+
+        assert_eq!(updated_skill.label, SkillLabel("Rust 2018".into()));
+        assert_eq!(updated_skill.version, added_skill.version.next());
+        assert_eq!(db.get(added_skill.id.clone())?, Some(updated_skill));
+
+        let result = db.update(UpdateSkillRequest {
+            skill_id: added_skill.id.clone(),
+            label: SkillLabel("Rust 2021".into()),
+            expected_version: Some(added_skill.version),
+        });
+        assert!(matches!(result, Err(UpdateSkillError::VersionConflict(_))));
+
+        let result = db.update(UpdateSkillRequest {
+            skill_id: SkillId(Uuid::new_v4()),
+            label: SkillLabel("Go".into()),
+            expected_version: None,
+        });
+        assert!(matches!(result, Err(UpdateSkillError::SkillNotFound(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_skills_test() -> skill_manager::Result<()> {
+        let mut db = SkillDb::default();
+        for label in &["rust", "JavaScript", "Java", "Go"] {
+            db.add(SkillLabel(label.to_string()))?;
+        }
+        let labels = |page: Page<Skill>| -> Vec<String> {
+            page.items.into_iter().map(|skill| skill.label.0).collect()
+        };
+
+        let page = db.find_skills(&SkillQuery::default())?;
+        assert_eq!(labels(page), vec!["Go", "Java", "JavaScript", "rust"]);
+
+        let page = db.find_skills(&Query {
+            filter: Some("JAVA".into()),
+            sort: SkillSortKey::Label,
+            direction: SortDirection::Descending,
+            page: PageRequest::default(),
+        })?;
+        assert_eq!(labels(page), vec!["JavaScript", "Java"]);
+
+        let page = db.find_skills(&Query {
+            page: PageRequest {
+                offset: 1,
+                limit: Some(2),
+            },
+            ..SkillQuery::default()
+        })?;
+        assert_eq!(page.total, 4);
+        assert_eq!(page.next_offset(), Some(3));
+        assert_eq!(labels(page), vec!["Java", "JavaScript"]);
+
+        Ok(())
+    }
 }
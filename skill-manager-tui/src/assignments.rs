@@ -0,0 +1,99 @@
+use crate::Result;
+use anyhow::anyhow;
+use skill_manager::{
+    employees::{
+        usecase::{
+            DeleteProjectAssignment, DeleteSkillKnowledgeOfEmployee, SetSkillKnowledgeOfEmployee,
+            SetSkillKnowledgeRequest,
+        },
+        Employee, ProjectAssignment, SkillAssignment, SkillLevel,
+    },
+    projects::{usecase::FindProjects, ProjectId, ProjectLabel},
+    skills::{usecase::FindSkills, SkillId, SkillLabel},
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+
+/// A skill or project of an employee, a row of the employee's detail pane.
+#[derive(Clone)]
+pub enum Assignment {
+    Skill(SkillAssignment),
+    Project(ProjectAssignment),
+}
+
+/// The assignments of an employee in the order of the detail pane: the skills by level, then
+/// the projects by start date.
+pub fn of(employee: &Employee) -> Vec<Assignment> {
+    let mut skills = employee.skills.clone();
+    skills.sort_by(|a, b| b.level.cmp(&a.level).then_with(|| a.label.cmp(&b.label)));
+    let mut projects = employee.projects.clone();
+    projects.sort_by_key(|project| project.start_date);
+    skills
+        .into_iter()
+        .map(Assignment::Skill)
+        .chain(projects.into_iter().map(Assignment::Project))
+        .collect()
+}
+
+/// Raises or lowers the level of a skill, not below 1.
+pub fn change_level(
+    db: &mut UnitOfWork,
+    employee: &Employee,
+    skill: &SkillAssignment,
+    distance: isize,
+) -> Result<()> {
+    let level = (skill.level.0 as isize + distance).max(1) as usize;
+    let skill_id = skill_id(db, &skill.label)?;
+    db.employees
+        .with(&*db.skills)
+        .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+            employee_id: employee.id.clone(),
+            skill_id,
+            level: SkillLevel(level),
+            secret: skill.secret,
+            expected_version: Some(employee.version),
+        })?;
+    Ok(())
+}
+
+pub fn unassign(db: &mut UnitOfWork, employee: &Employee, assignment: &Assignment) -> Result<()> {
+    match assignment {
+        Assignment::Skill(skill) => {
+            let skill_id = skill_id(db, &skill.label)?;
+            db.employees
+                .with(&*db.skills)
+                .delete_skill_knowledge_of_employee(
+                    employee.id.clone(),
+                    skill_id,
+                    Some(employee.version),
+                )?;
+        }
+        Assignment::Project(project) => db.employees.delete_project_assignment(
+            employee.id.clone(),
+            project.id.clone(),
+            Some(employee.version),
+        )?,
+    }
+    Ok(())
+}
+
+/// The skill with a label, ignoring case.
+pub fn skill_id(db: &UnitOfWork, label: &SkillLabel) -> Result<SkillId> {
+    db.skills
+        .find_skills(&Default::default())?
+        .items
+        .into_iter()
+        .find(|skill| skill.label.0.to_lowercase() == label.0.to_lowercase())
+        .map(|skill| skill.id)
+        .ok_or_else(|| anyhow!("Skill {} not found", label))
+}
+
+/// The project with a label, ignoring case.
+pub fn project_id(db: &UnitOfWork, label: &ProjectLabel) -> Result<ProjectId> {
+    db.projects
+        .find_projects(&Default::default())?
+        .items
+        .into_iter()
+        .find(|project| project.label.0.to_lowercase() == label.0.to_lowercase())
+        .map(|project| project.id)
+        .ok_or_else(|| anyhow!("Project {} not found", label))
+}
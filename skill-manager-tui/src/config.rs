@@ -0,0 +1,29 @@
+use anyhow::Result;
+use serde::Deserialize;
+use skill_manager_file::{
+    backend::{Backend, StorageConfig},
+    unit_of_work::UnitOfWork,
+};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Selects the data like the CLI does, from the same flags, environment variables and config
+/// file.
+#[derive(StructOpt)]
+pub struct ConfigArgs {
+    #[structopt(long = "config", parse(from_os_str))]
+    config: Option<PathBuf>,
+    #[structopt(long = "data-dir", env = "SKILL_MANAGER_DATA_DIR", parse(from_os_str))]
+    data_dir: Option<PathBuf>,
+    #[structopt(long = "backend")]
+    backend: Option<Backend>,
+}
+
+/// The TUI has no settings of its own, it ignores the other settings of the CLI's config file.
+#[derive(Default, Deserialize)]
+struct Settings {}
+
+pub fn open_unit_of_work(args: ConfigArgs) -> Result<UnitOfWork> {
+    let (storage, Settings {}) = StorageConfig::resolve(args.config, args.data_dir, args.backend)?;
+    storage.open()
+}
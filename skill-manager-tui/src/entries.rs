@@ -0,0 +1,293 @@
+use crate::{
+    assignments::{self, Assignment},
+    Header, Result,
+};
+use skill_manager::{
+    employees::{
+        usecase::{DeleteEmployeeById, FindEmployees},
+        Employee, ProjectAssignment,
+    },
+    projects::{
+        usecase::{DeleteProject, FindProjects},
+        Project,
+    },
+    skills::{
+        usecase::{DeleteSkillById, FindSkills},
+        Skill,
+    },
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use time::Date;
+use tui::{
+    style::{Color, Modifier, Style},
+    widgets::Text,
+};
+
+/// An entry of a tab's list.
+#[derive(Clone)]
+pub enum Entry {
+    Skill(Skill),
+    Project(Project),
+    Employee(Employee),
+}
+
+/// The entries of a tab in the order of their list.
+pub fn load(tab: Header, db: &UnitOfWork) -> Result<Vec<Entry>> {
+    Ok(match tab {
+        Header::Skills => db
+            .skills
+            .find_skills(&Default::default())?
+            .items
+            .into_iter()
+            .map(Entry::Skill)
+            .collect(),
+        Header::Projects => db
+            .projects
+            .find_projects(&Default::default())?
+            .items
+            .into_iter()
+            .map(Entry::Project)
+            .collect(),
+        // The matrix and the timeline show the employees.
+        Header::Employees | Header::Matrix | Header::Timeline => db
+            .employees
+            .find_employees(&Default::default())?
+            .items
+            .into_iter()
+            .map(Entry::Employee)
+            .collect(),
+    })
+}
+
+impl Entry {
+    pub fn label(&self) -> String {
+        match self {
+            Entry::Skill(skill) => skill.label.to_string(),
+            Entry::Project(project) => project.label.to_string(),
+            Entry::Employee(employee) => full_name(employee),
+        }
+    }
+
+    /// Whether both entries are versions of the same record.
+    pub fn is_same(&self, other: &Entry) -> bool {
+        match (self, other) {
+            (Entry::Skill(a), Entry::Skill(b)) => a.id == b.id,
+            (Entry::Project(a), Entry::Project(b)) => a.id == b.id,
+            (Entry::Employee(a), Entry::Employee(b)) => a.id == b.id,
+            _ => false,
+        }
+    }
+
+    /// The lines of the detail pane: an employee's skills and project assignments, the
+    /// holders of a skill or the team of a project. `focus` is the selected assignment of an
+    /// employee whose detail pane has the focus.
+    pub fn details(&self, db: &UnitOfWork, focus: Option<usize>) -> Result<Vec<Text<'static>>> {
+        let mut lines = vec![];
+        match self {
+            Entry::Employee(employee) => {
+                for (name, value) in &[
+                    ("Title", &employee.title.0),
+                    ("Email", &employee.email.0),
+                    ("Telephone", &employee.telephone.0),
+                ] {
+                    if !value.is_empty() {
+                        lines.push(Text::raw(format!("{}: {}\n", name, value)));
+                    }
+                }
+                push_heading(&mut lines, "Skills");
+                let assignments = assignments::of(employee);
+                let first_project = assignments
+                    .iter()
+                    .position(|assignment| matches!(assignment, Assignment::Project(_)));
+                for (ix, assignment) in assignments.iter().enumerate() {
+                    if Some(ix) == first_project {
+                        push_heading(&mut lines, "Projects");
+                    }
+                    let line = match assignment {
+                        Assignment::Skill(skill) => {
+                            let secret = if skill.secret { " (secret)" } else { "" };
+                            format!("{} {}{}\n", skill.label, skill.level, secret)
+                        }
+                        Assignment::Project(project) => format!(
+                            "{} ({}), {}\n",
+                            project.label,
+                            period(project),
+                            project.contribution
+                        ),
+                    };
+                    lines.push(if focus == Some(ix) {
+                        Text::styled(line, Style::default().fg(Color::Black).bg(Color::Yellow))
+                    } else {
+                        Text::raw(line)
+                    });
+                }
+                if first_project.is_none() {
+                    push_heading(&mut lines, "Projects");
+                }
+            }
+            Entry::Skill(skill) => {
+                push_heading(&mut lines, "Employees");
+                let mut holders: Vec<_> = employees(db)?
+                    .into_iter()
+                    .filter_map(|employee| {
+                        let level = employee
+                            .skills
+                            .iter()
+                            .find(|assignment| assignment.label == skill.label)?
+                            .level
+                            .0;
+                        Some((level, full_name(&employee)))
+                    })
+                    .collect();
+                holders.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+                for (level, name) in holders {
+                    lines.push(Text::raw(format!("{} {}\n", name, level)));
+                }
+            }
+            Entry::Project(project) => {
+                if !project.description.0.is_empty() {
+                    lines.push(Text::raw(format!("{}\n", project.description)));
+                }
+                push_heading(&mut lines, "Team");
+                let mut team: Vec<_> = employees(db)?
+                    .iter()
+                    .flat_map(|employee| {
+                        employee
+                            .projects
+                            .iter()
+                            .filter(|assignment| assignment.label == project.label)
+                            .map(move |assignment| {
+                                (
+                                    assignment.start_date,
+                                    format!(
+                                        "{} ({}), {}\n",
+                                        full_name(employee),
+                                        period(assignment),
+                                        assignment.contribution
+                                    ),
+                                )
+                            })
+                    })
+                    .collect();
+                team.sort();
+                lines.extend(team.into_iter().map(|(_, line)| Text::raw(line)));
+            }
+        }
+        Ok(lines)
+    }
+
+    /// The lines of the confirmation before deleting the entry, with the data depending on
+    /// it.
+    pub fn dependents(&self, db: &UnitOfWork) -> Result<Vec<Text<'static>>> {
+        let mut lines = vec![];
+        let holders = |assigned: &dyn Fn(&Employee) -> bool| -> Result<Vec<String>> {
+            Ok(employees(db)?
+                .iter()
+                .filter(|employee| assigned(employee))
+                .map(full_name)
+                .collect())
+        };
+        let names = match self {
+            Entry::Employee(employee) => {
+                lines.push(Text::raw(format!(
+                    "The {} and {} of {} are deleted with the employee.\n",
+                    count(employee.skills.len(), "skill assignment"),
+                    count(employee.projects.len(), "project assignment"),
+                    full_name(employee)
+                )));
+                vec![]
+            }
+            Entry::Skill(skill) => {
+                let names = holders(&|employee| {
+                    employee
+                        .skills
+                        .iter()
+                        .any(|assignment| assignment.label == skill.label)
+                })?;
+                lines.push(Text::raw(format!(
+                    "{} is held by {}.\n",
+                    skill.label,
+                    count(names.len(), "employee")
+                )));
+                names
+            }
+            Entry::Project(project) => {
+                let names = holders(&|employee| {
+                    employee
+                        .projects
+                        .iter()
+                        .any(|assignment| assignment.label == project.label)
+                })?;
+                lines.push(Text::raw(format!(
+                    "{} is assigned to {}.\n",
+                    project.label,
+                    count(names.len(), "employee")
+                )));
+                names
+            }
+        };
+        if !names.is_empty() {
+            lines.push(Text::raw("Their assignments are kept.\n"));
+            push_heading(&mut lines, "Employees");
+            lines.extend(
+                names
+                    .into_iter()
+                    .map(|name| Text::raw(format!("{}\n", name))),
+            );
+        }
+        Ok(lines)
+    }
+
+    /// Deletes the entry unless it changed since it was loaded.
+    pub fn delete(&self, db: &mut UnitOfWork) -> Result<()> {
+        match self {
+            Entry::Skill(skill) => db.skills.delete(skill.id.clone(), Some(skill.version))?,
+            Entry::Project(project) => db
+                .projects
+                .delete(project.id.clone(), Some(project.version))?,
+            Entry::Employee(employee) => db
+                .employees
+                .delete(employee.id.clone(), Some(employee.version))?,
+        }
+        Ok(())
+    }
+}
+
+fn employees(db: &UnitOfWork) -> Result<Vec<Employee>> {
+    Ok(db.employees.find_employees(&Default::default())?.items)
+}
+
+/// Pushes a bold heading, separated by a blank line from the lines before it.
+fn push_heading(lines: &mut Vec<Text<'static>>, title: &'static str) {
+    if !lines.is_empty() {
+        lines.push(Text::raw("\n"));
+    }
+    lines.push(Text::styled(
+        format!("{}\n", title),
+        Style::default().modifier(Modifier::BOLD),
+    ));
+}
+
+/// A number of things, e.g. "1 employee" or "2 employees".
+fn count(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
+
+fn full_name(employee: &Employee) -> String {
+    format!("{} {}", employee.first_name, employee.last_name)
+}
+
+fn period(assignment: &ProjectAssignment) -> String {
+    format!(
+        "{} - {}",
+        format_date(assignment.start_date),
+        assignment
+            .end_date
+            .map(format_date)
+            .unwrap_or_else(|| "today".to_string())
+    )
+}
+
+fn format_date(date: Date) -> String {
+    date.format("%F")
+}
@@ -0,0 +1,201 @@
+use crate::entries::Entry;
+
+/// The filter of a list typed after `/`: whitespace separated terms that all have to match.
+///
+/// A term like `rust>=3` is a quick filter for the employees knowing a skill whose label
+/// contains `rust` at level 3 or higher. Other terms match labels fuzzily, and for employees
+/// also the labels of their skills.
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+enum Term {
+    Fuzzy(String),
+    SkillLevel { skill: String, level: usize },
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Self {
+        let terms = input
+            .split_whitespace()
+            .map(|term| {
+                let quick = term
+                    .find(">=")
+                    .map(|ix| (&term[..ix], &term[ix + 2..]))
+                    .or_else(|| {
+                        term.find('≥')
+                            .map(|ix| (&term[..ix], &term[ix + '≥'.len_utf8()..]))
+                    });
+                match quick {
+                    Some((skill, level)) if !skill.is_empty() => match level.parse() {
+                        Ok(level) => Term::SkillLevel {
+                            skill: skill.to_lowercase(),
+                            level,
+                        },
+                        Err(_) => Term::Fuzzy(skill.to_string()),
+                    },
+                    _ => Term::Fuzzy(term.to_string()),
+                }
+            })
+            .collect();
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let label = entry.label();
+        self.terms.iter().all(|term| match (term, entry) {
+            (Term::Fuzzy(pattern), Entry::Employee(employee)) => {
+                fuzzy_match(pattern, &label).is_some()
+                    || employee
+                        .skills
+                        .iter()
+                        .any(|skill| fuzzy_match(pattern, &skill.label.0).is_some())
+            }
+            (Term::Fuzzy(pattern), _) => fuzzy_match(pattern, &label).is_some(),
+            (Term::SkillLevel { skill, level }, Entry::Employee(employee)) => {
+                employee.skills.iter().any(|assignment| {
+                    assignment.level.0 >= *level
+                        && assignment.label.0.to_lowercase().contains(skill.as_str())
+                })
+            }
+            // Skills and projects have no levels, so only the skill of a quick filter counts.
+            (Term::SkillLevel { skill, .. }, _) => label.to_lowercase().contains(skill.as_str()),
+        })
+    }
+
+    /// The indices of the characters of a label matched by the filter, to highlight them.
+    pub fn highlights(&self, label: &str) -> Vec<usize> {
+        let mut highlights: Vec<usize> = self
+            .terms
+            .iter()
+            .filter_map(|term| match term {
+                Term::Fuzzy(pattern) => fuzzy_match(pattern, label),
+                Term::SkillLevel { .. } => None,
+            })
+            .flatten()
+            .collect();
+        highlights.sort_unstable();
+        highlights.dedup();
+        highlights
+    }
+}
+
+/// The indices of the characters of `text` that contain the characters of `pattern` in
+/// order, ignoring case, if all of them are found.
+fn fuzzy_match(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let mut text = text.chars().enumerate();
+    pattern
+        .chars()
+        .map(|p| {
+            text.find(|(_, c)| c.to_lowercase().eq(p.to_lowercase()))
+                .map(|(ix, _)| ix)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{
+            EmailAddress, Employee, FirstName, LastName, SkillAssignment, SkillLevel,
+            TelephoneNumber, Title,
+        },
+        skills::{Skill, SkillLabel},
+        version::Version,
+    };
+    use time::OffsetDateTime;
+
+    fn employee(first_name: &str, last_name: &str, skills: &[(&str, usize)]) -> Entry {
+        Entry::Employee(Employee {
+            id: "9f6d3c1e-2b8a-4a8e-9c56-3f1e0d7b2a41".parse().unwrap(),
+            first_name: FirstName(first_name.into()),
+            last_name: LastName(last_name.into()),
+            title: Title("".into()),
+            email: EmailAddress("".into()),
+            telephone: TelephoneNumber("".into()),
+            skills: skills
+                .iter()
+                .map(|&(label, level)| SkillAssignment {
+                    label: SkillLabel(label.into()),
+                    level: SkillLevel(level),
+                    secret: false,
+                })
+                .collect(),
+            projects: vec![],
+            last_update: OffsetDateTime::now_utc(),
+            version: Version::default(),
+        })
+    }
+
+    fn skill(label: &str) -> Entry {
+        Entry::Skill(Skill {
+            id: "0b6a7f4e-8d1c-4f2a-9e3b-5c7d9a1b2c3d".parse().unwrap(),
+            label: SkillLabel(label.into()),
+            version: Version::default(),
+        })
+    }
+
+    #[test]
+    fn skill_level_test() {
+        let filter = Filter::parse("rust>=3");
+        assert!(filter.matches(&employee("Ada", "Lovelace", &[("Rust", 4)])));
+        assert!(filter.matches(&employee("Ada", "Lovelace", &[("Trusty Rust", 3)])));
+        assert!(!filter.matches(&employee("Ada", "Lovelace", &[("Rust", 2)])));
+        assert!(!filter.matches(&employee("Rust", "Lovelace", &[])));
+        assert!(filter.matches(&skill("Rust")));
+        assert!(!filter.matches(&skill("Go")));
+        assert!(filter.highlights("Rust").is_empty());
+
+        let filter = Filter::parse("RUST≥3");
+        assert!(filter.matches(&employee("Ada", "Lovelace", &[("Rust", 3)])));
+        assert!(!filter.matches(&employee("Ada", "Lovelace", &[("Rust", 2)])));
+    }
+
+    #[test]
+    fn invalid_level_falls_back_to_fuzzy_test() {
+        let filter = Filter::parse("rust≥x");
+        assert!(filter.matches(&employee("Ada", "Lovelace", &[("Rust", 1)])));
+        assert!(filter.matches(&skill("Rust")));
+        assert_eq!(filter.highlights("Rust"), vec![0, 1, 2, 3]);
+
+        let filter = Filter::parse(">=3");
+        assert!(!filter.is_empty());
+        assert!(!filter.matches(&employee("Ada", "Lovelace", &[("Rust", 5)])));
+        assert!(filter.matches(&skill("a >= 3")));
+    }
+
+    #[test]
+    fn all_terms_have_to_match_test() {
+        let filter = Filter::parse("  ada   rust>=3 ");
+        assert!(filter.matches(&employee("Ada", "Lovelace", &[("Rust", 3)])));
+        assert!(!filter.matches(&employee("Ada", "Lovelace", &[("Go", 3)])));
+        assert!(!filter.matches(&employee("Grace", "Hopper", &[("Rust", 3)])));
+        assert!(Filter::parse(" ").is_empty());
+        assert!(Filter::parse("").matches(&skill("Rust")));
+
+        let filter = Filter::parse("ada go");
+        assert!(filter.matches(&employee("Ada", "Lovelace", &[("Golang", 1)])));
+        assert!(!filter.matches(&employee("Ada", "Lovelace", &[("Rust", 1)])));
+    }
+
+    #[test]
+    fn highlights_test() {
+        assert_eq!(Filter::parse("jrg").highlights("Jürgen"), vec![0, 2, 3]);
+        assert_eq!(Filter::parse("ÜR").highlights("Jürgen"), vec![1, 2]);
+        assert_eq!(
+            Filter::parse("jü gen").highlights("Jürgen"),
+            vec![0, 1, 3, 4, 5]
+        );
+        assert_eq!(
+            Filter::parse("xyz").highlights("Jürgen"),
+            Vec::<usize>::new()
+        );
+        assert_eq!(fuzzy_match("rg", "Jürgen"), Some(vec![2, 3]));
+        assert_eq!(fuzzy_match("gr", "Jürgen"), None);
+    }
+}
@@ -0,0 +1,619 @@
+use crate::{
+    assignments,
+    entries::{self, Entry},
+    Header, Result,
+};
+use anyhow::anyhow;
+use skill_manager::{
+    employees::{
+        usecase::{
+            AddEmployee, AddEmployeeRequest, CreateProjectAssignment,
+            DeleteSkillKnowledgeOfEmployee, GetEmployeeById, ProjectAssignmentRequest,
+            SetSkillKnowledgeOfEmployee, SetSkillKnowledgeRequest, UpdateEmployee,
+            UpdateEmployeeRequest,
+        },
+        EmailAddress, Employee, FirstName, LastName, ProjectContribution, SkillAssignment,
+        SkillLevel, TelephoneNumber, Title,
+    },
+    projects::{
+        usecase::{AddProject, FindProjects, UpdateProject, UpdateProjectRequest},
+        ProjectDescription, ProjectLabel,
+    },
+    skills::{
+        usecase::{AddSkill, FindSkills, UpdateSkill, UpdateSkillRequest},
+        SkillLabel,
+    },
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use time::{Date, OffsetDateTime};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Text, Widget},
+    Frame,
+};
+
+/// A form to create or edit an entry of a tab, or to assign a skill or project to an
+/// employee.
+pub struct Form {
+    target: Target,
+    fields: Vec<Field>,
+    focus: usize,
+    /// Whether the form was submitted before, after which it is validated on every change.
+    submitted: bool,
+    /// The error of the last submit that does not belong to a single field.
+    error: Option<String>,
+}
+
+enum Target {
+    New(Header),
+    Edit(Entry),
+    /// Assigns a skill to the employee, replacing the given assignment.
+    SkillAssignment(Employee, Option<SkillAssignment>),
+    ProjectAssignment(Employee),
+}
+
+struct Field {
+    label: &'static str,
+    value: String,
+    required: bool,
+    kind: Kind,
+    error: Option<String>,
+}
+
+enum Kind {
+    Text,
+    /// One of the values, completed with the right arrow key.
+    Choice(Vec<String>),
+    /// Yes or no, switched with space.
+    Toggle,
+}
+
+const YES: &str = "yes";
+const NO: &str = "no";
+
+impl Field {
+    fn new(label: &'static str, required: bool) -> Self {
+        Self {
+            label,
+            value: String::new(),
+            required,
+            kind: Kind::Text,
+            error: None,
+        }
+    }
+
+    fn choice(label: &'static str, choices: Vec<String>) -> Self {
+        Self {
+            kind: Kind::Choice(choices),
+            ..Self::new(label, true)
+        }
+    }
+
+    fn toggle(label: &'static str, on: bool) -> Self {
+        Self {
+            kind: Kind::Toggle,
+            ..Self::new(label, false).with_value(if on { YES } else { NO })
+        }
+    }
+
+    fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self
+    }
+
+    /// The choices starting with the value, ignoring case.
+    fn matches(&self) -> Vec<&String> {
+        match &self.kind {
+            Kind::Choice(choices) => {
+                let value = self.value.trim().to_lowercase();
+                choices
+                    .iter()
+                    .filter(|choice| choice.to_lowercase().starts_with(&value))
+                    .collect()
+            }
+            Kind::Text | Kind::Toggle => vec![],
+        }
+    }
+}
+
+impl Form {
+    pub fn new(tab: Header) -> Self {
+        let fields = match tab {
+            Header::Skills => vec![Field::new("Label", true)],
+            Header::Projects => vec![Field::new("Label", true), Field::new("Description", false)],
+            Header::Employees | Header::Matrix | Header::Timeline => vec![
+                Field::new("First name", true),
+                Field::new("Last name", true),
+                Field::new("Title", false),
+                Field::new("Email", false),
+                Field::new("Telephone", false),
+            ],
+        };
+        Self::with_fields(Target::New(tab), fields)
+    }
+
+    /// A form prefilled with the values of an existing entry.
+    pub fn edit(entry: Entry) -> Self {
+        let (tab, values) = match &entry {
+            Entry::Skill(skill) => (Header::Skills, vec![skill.label.0.clone()]),
+            Entry::Project(project) => (
+                Header::Projects,
+                vec![project.label.0.clone(), project.description.0.clone()],
+            ),
+            Entry::Employee(employee) => (
+                Header::Employees,
+                vec![
+                    employee.first_name.0.clone(),
+                    employee.last_name.0.clone(),
+                    employee.title.0.clone(),
+                    employee.email.0.clone(),
+                    employee.telephone.0.clone(),
+                ],
+            ),
+        };
+        let mut form = Self::new(tab);
+        for (field, value) in form.fields.iter_mut().zip(values) {
+            field.value = value;
+        }
+        form.target = Target::Edit(entry);
+        form
+    }
+
+    /// A form to assign a skill to an employee, or to change an assigned skill.
+    pub fn assign_skill(
+        employee: Employee,
+        assigned: Option<SkillAssignment>,
+        db: &UnitOfWork,
+    ) -> Result<Self> {
+        let skills = db
+            .skills
+            .find_skills(&Default::default())?
+            .items
+            .into_iter()
+            .map(|skill| skill.label.0)
+            .collect();
+        let fields = match &assigned {
+            Some(skill) => vec![
+                Field::choice("Skill", skills).with_value(skill.label.0.clone()),
+                Field::new("Level", true).with_value(skill.level.to_string()),
+                Field::toggle("Secret", skill.secret),
+            ],
+            None => vec![
+                Field::choice("Skill", skills),
+                Field::new("Level", true),
+                Field::toggle("Secret", false),
+            ],
+        };
+        Ok(Self::with_fields(
+            Target::SkillAssignment(employee, assigned),
+            fields,
+        ))
+    }
+
+    /// A form to assign a project to an employee, starting today unless changed.
+    pub fn assign_project(employee: Employee, db: &UnitOfWork) -> Result<Self> {
+        let projects = db
+            .projects
+            .find_projects(&Default::default())?
+            .items
+            .into_iter()
+            .map(|project| project.label.0)
+            .collect();
+        let today = OffsetDateTime::now_utc().date().format("%F");
+        let fields = vec![
+            Field::choice("Project", projects),
+            Field::new("Contribution", false),
+            Field::new("Start date", true).with_value(today),
+            Field::new("End date", false),
+        ];
+        Ok(Self::with_fields(
+            Target::ProjectAssignment(employee),
+            fields,
+        ))
+    }
+
+    fn with_fields(target: Target, fields: Vec<Field>) -> Self {
+        Self {
+            target,
+            fields,
+            focus: 0,
+            submitted: false,
+            error: None,
+        }
+    }
+
+    /// Whether the form assigns a skill or project rather than saving an entry of a tab.
+    pub fn assigns(&self) -> bool {
+        match self.target {
+            Target::SkillAssignment(..) | Target::ProjectAssignment(_) => true,
+            Target::New(_) | Target::Edit(_) => false,
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % self.fields.len();
+    }
+
+    pub fn focus_previous(&mut self) {
+        self.focus = (self.focus + self.fields.len() - 1) % self.fields.len();
+    }
+
+    pub fn push(&mut self, c: char) {
+        let field = &mut self.fields[self.focus];
+        match field.kind {
+            Kind::Toggle if c == ' ' => {
+                field.value = if field.value == YES { NO } else { YES }.to_string()
+            }
+            Kind::Toggle => {}
+            Kind::Text | Kind::Choice(_) => field.value.push(c),
+        }
+    }
+
+    pub fn pop(&mut self) {
+        let field = &mut self.fields[self.focus];
+        if let Kind::Text | Kind::Choice(_) = field.kind {
+            field.value.pop();
+        }
+    }
+
+    /// Completes the focused field to its first matching choice.
+    pub fn complete(&mut self) {
+        let field = &self.fields[self.focus];
+        if let Some(choice) = field.matches().first().map(|choice| choice.to_string()) {
+            self.fields[self.focus].value = choice;
+        }
+    }
+
+    /// Updates the errors after a change, once the form was submitted.
+    pub fn revalidate(&mut self, db: &UnitOfWork) -> Result<()> {
+        if self.submitted {
+            self.validate(db)?;
+        }
+        Ok(())
+    }
+
+    /// Validates and applies the form, returning the saved entry, the employee for
+    /// assignments. Errors of the use cases are shown in the form instead of being returned.
+    pub fn submit(&mut self, db: &mut UnitOfWork) -> Result<Option<Entry>> {
+        self.submitted = true;
+        self.error = None;
+        if !self.validate(db)? {
+            return Ok(None);
+        }
+        match self.apply(db) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Ok(None)
+            }
+        }
+    }
+
+    fn value(&self, ix: usize) -> String {
+        self.fields[ix].value.trim().to_string()
+    }
+
+    /// The choice a field's value names, ignoring case.
+    fn choice(&self, ix: usize) -> Option<String> {
+        let value = self.value(ix).to_lowercase();
+        match &self.fields[ix].kind {
+            Kind::Choice(choices) => choices
+                .iter()
+                .find(|choice| choice.to_lowercase() == value)
+                .cloned(),
+            Kind::Text | Kind::Toggle => None,
+        }
+    }
+
+    /// Checks the required fields, that labels and email addresses are unique, and that
+    /// choices, levels and dates are valid. Returns whether the form is valid.
+    fn validate(&mut self, db: &UnitOfWork) -> Result<bool> {
+        for field in &mut self.fields {
+            field.error = if field.required && field.value.trim().is_empty() {
+                Some("Required".into())
+            } else {
+                None
+            };
+        }
+        match &self.target {
+            Target::New(tab) => self.validate_entry(*tab, None, db)?,
+            Target::Edit(entry) => {
+                let tab = match entry {
+                    Entry::Skill(_) => Header::Skills,
+                    Entry::Project(_) => Header::Projects,
+                    Entry::Employee(_) => Header::Employees,
+                };
+                let entry = entry.clone();
+                self.validate_entry(tab, Some(entry), db)?
+            }
+            Target::SkillAssignment(..) => {
+                if self.choice(0).is_none() {
+                    self.set_error(0, "Unknown skill");
+                }
+                match self.value(1).parse::<usize>() {
+                    Ok(level) if level >= 1 => {}
+                    _ => self.set_error(1, "A level of at least 1"),
+                }
+            }
+            Target::ProjectAssignment(_) => {
+                if self.choice(0).is_none() {
+                    self.set_error(0, "Unknown project");
+                }
+                match (parse_date(&self.value(2)), parse_date(&self.value(3))) {
+                    (Err(e), _) => self.set_error(2, &e),
+                    (_, Err(e)) => self.set_error(3, &e),
+                    (Ok(Some(start_date)), Ok(Some(end_date))) if end_date < start_date => {
+                        self.set_error(3, "Before the start date")
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(self.fields.iter().all(|field| field.error.is_none()))
+    }
+
+    /// Checks that the label of a skill or project, or the email address of an employee, is
+    /// not used by another entry than the edited one.
+    fn validate_entry(
+        &mut self,
+        tab: Header,
+        original: Option<Entry>,
+        db: &UnitOfWork,
+    ) -> Result<()> {
+        let others: Vec<Entry> = entries::load(tab, db)?
+            .into_iter()
+            .filter(|entry| match &original {
+                Some(original) => !original.is_same(entry),
+                None => true,
+            })
+            .collect();
+        match tab {
+            Header::Skills | Header::Projects => {
+                let label = self.value(0).to_lowercase();
+                if others
+                    .iter()
+                    .any(|other| other.label().to_lowercase() == label)
+                {
+                    let noun = noun(tab).to_lowercase();
+                    self.set_error(0, &format!("A {} with this label exists already", noun));
+                }
+            }
+            Header::Employees | Header::Matrix | Header::Timeline => {
+                let email = self.value(3).to_lowercase();
+                if email.is_empty() {
+                    if self.value(4).is_empty() {
+                        self.set_error(3, "Email or telephone required");
+                    }
+                } else if !email.contains('@') {
+                    self.set_error(3, "Not an email address");
+                } else if let Some(other) = others.iter().find(|other| match other {
+                    Entry::Employee(employee) => employee.email.0.to_lowercase() == email,
+                    _ => false,
+                }) {
+                    self.set_error(3, &format!("Used by {}", other.label()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the error of a field unless it has one already.
+    fn set_error(&mut self, ix: usize, error: &str) {
+        self.fields[ix]
+            .error
+            .get_or_insert_with(|| error.to_string());
+    }
+
+    fn apply(&self, db: &mut UnitOfWork) -> Result<Entry> {
+        Ok(match &self.target {
+            Target::New(Header::Skills) => Entry::Skill(db.skills.add(SkillLabel(self.value(0)))?),
+            Target::New(Header::Projects) => Entry::Project(db.projects.add(
+                ProjectLabel(self.value(0)),
+                ProjectDescription(self.value(1)),
+            )?),
+            Target::New(Header::Employees)
+            | Target::New(Header::Matrix)
+            | Target::New(Header::Timeline) => {
+                Entry::Employee(db.employees.add(AddEmployeeRequest {
+                    first_name: FirstName(self.value(0)),
+                    last_name: LastName(self.value(1)),
+                    title: Title(self.value(2)),
+                    email: EmailAddress(self.value(3)),
+                    telephone: TelephoneNumber(self.value(4)),
+                })?)
+            }
+            Target::Edit(Entry::Skill(skill)) => {
+                let updated = db.skills.update(UpdateSkillRequest {
+                    skill_id: skill.id.clone(),
+                    label: SkillLabel(self.value(0)),
+                    expected_version: Some(skill.version),
+                })?;
+                db.employees.rename_skill(&skill.label, &updated);
+                Entry::Skill(updated)
+            }
+            Target::Edit(Entry::Project(project)) => {
+                let updated = db.projects.update(UpdateProjectRequest {
+                    project_id: project.id.clone(),
+                    label: ProjectLabel(self.value(0)),
+                    description: ProjectDescription(self.value(1)),
+                    expected_version: Some(project.version),
+                })?;
+                db.employees.update_project(&project.label, &updated);
+                Entry::Project(updated)
+            }
+            Target::Edit(Entry::Employee(employee)) => {
+                Entry::Employee(db.employees.update(UpdateEmployeeRequest {
+                    employee_id: employee.id.clone(),
+                    first_name: FirstName(self.value(0)),
+                    last_name: LastName(self.value(1)),
+                    title: Title(self.value(2)),
+                    email: EmailAddress(self.value(3)),
+                    telephone: TelephoneNumber(self.value(4)),
+                    expected_version: Some(employee.version),
+                })?)
+            }
+            Target::SkillAssignment(employee, assigned) => {
+                let label = SkillLabel(self.choice(0).unwrap_or_default());
+                let skill_id = assignments::skill_id(db, &label)?;
+                db.employees
+                    .with(&*db.skills)
+                    .set_skill_knowledge_of_employee(SetSkillKnowledgeRequest {
+                        employee_id: employee.id.clone(),
+                        skill_id,
+                        level: SkillLevel(self.value(1).parse()?),
+                        secret: self.value(2) == YES,
+                        expected_version: Some(employee.version),
+                    })?;
+                // Picking another skill replaces the changed one.
+                if let Some(previous) = assigned.iter().find(|previous| previous.label != label) {
+                    let previous_id = assignments::skill_id(db, &previous.label)?;
+                    db.employees
+                        .with(&*db.skills)
+                        .delete_skill_knowledge_of_employee(
+                            employee.id.clone(),
+                            previous_id,
+                            None,
+                        )?;
+                }
+                reload(db, employee)?
+            }
+            Target::ProjectAssignment(employee) => {
+                let label = ProjectLabel(self.choice(0).unwrap_or_default());
+                let project_id = assignments::project_id(db, &label)?;
+                let start_date = parse_date(&self.value(2))
+                    .map_err(|e| anyhow!(e))?
+                    .ok_or_else(|| anyhow!("Start date required"))?;
+                db.employees.with(&*db.projects).create_project_assignment(
+                    ProjectAssignmentRequest {
+                        employee_id: employee.id.clone(),
+                        project_id,
+                        contribution: ProjectContribution(self.value(1)),
+                        start_date,
+                        end_date: parse_date(&self.value(3)).map_err(|e| anyhow!(e))?,
+                        expected_version: Some(employee.version),
+                    },
+                )?;
+                reload(db, employee)?
+            }
+        })
+    }
+
+    fn title(&self) -> String {
+        let name = |employee: &Employee| format!("{} {}", employee.first_name, employee.last_name);
+        match &self.target {
+            Target::New(tab) => format!("New {}", noun(*tab)),
+            Target::Edit(Entry::Skill(_)) => "Edit Skill".to_string(),
+            Target::Edit(Entry::Project(_)) => "Edit Project".to_string(),
+            Target::Edit(Entry::Employee(_)) => "Edit Employee".to_string(),
+            Target::SkillAssignment(employee, Some(_)) => {
+                format!("Change Skill of {}", name(employee))
+            }
+            Target::SkillAssignment(employee, None) => {
+                format!("Assign Skill to {}", name(employee))
+            }
+            Target::ProjectAssignment(employee) => format!("Assign Project to {}", name(employee)),
+        }
+    }
+
+    /// Renders the form and returns the position of the cursor at the end of the focused
+    /// field.
+    pub fn render(&self, f: &mut Frame<impl Backend>, chunk: Rect) -> (u16, u16) {
+        let error_style = Style::default().fg(Color::Red);
+        let mut lines = vec![];
+        let mut row = 0;
+        if let Some(error) = &self.error {
+            lines.push(Text::styled(format!("{}\n\n", error), error_style));
+            row += 2;
+        }
+        // The labels with a colon and a marker for required fields.
+        let labels: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| format!("{}{}:", field.label, if field.required { "*" } else { "" }))
+            .collect();
+        let width = labels.iter().map(String::len).max().unwrap_or(0) + 1;
+        let mut cursor = (chunk.left(), chunk.top());
+        for (ix, (field, label)) in self.fields.iter().zip(labels).enumerate() {
+            let value = match field.kind {
+                Kind::Toggle if field.value == YES => "[x]".to_string(),
+                Kind::Toggle => "[ ]".to_string(),
+                Kind::Text | Kind::Choice(_) => field.value.clone(),
+            };
+            let style = if ix == self.focus {
+                // The block's title and left border come before the fields, the cursor of a
+                // toggle is between its brackets.
+                let offset = match field.kind {
+                    Kind::Toggle => 1,
+                    Kind::Text | Kind::Choice(_) => value.chars().count(),
+                };
+                cursor = (
+                    chunk.left() + 1 + (width + offset) as u16,
+                    chunk.top() + 1 + row,
+                );
+                Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.push(Text::styled(format!("{:<w$}", label, w = width), style));
+            lines.push(Text::raw(format!("{}\n", value)));
+            row += 1;
+            if let Some(error) = &field.error {
+                lines.push(Text::styled(
+                    format!("{:w$}{}\n", "", error, w = width),
+                    error_style,
+                ));
+                row += 1;
+            } else if ix == self.focus {
+                // The choices matching the value so far.
+                let matches = field.matches();
+                if !matches.is_empty() {
+                    let shown: Vec<&str> = matches.iter().take(5).map(|m| m.as_str()).collect();
+                    let more = if matches.len() > shown.len() {
+                        ", …"
+                    } else {
+                        ""
+                    };
+                    lines.push(Text::styled(
+                        format!("{:w$}{}{}\n", "", shown.join(", "), more, w = width),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                    row += 1;
+                }
+            }
+        }
+        Paragraph::new(lines.iter())
+            .block(Block::default().title(&self.title()).borders(Borders::LEFT))
+            .render(f, chunk);
+
+        (cursor.0.min(chunk.right().saturating_sub(1)), cursor.1)
+    }
+}
+
+/// The employee after an assignment was saved.
+fn reload(db: &UnitOfWork, employee: &Employee) -> Result<Entry> {
+    db.employees
+        .get(employee.id.clone())?
+        .map(Entry::Employee)
+        .ok_or_else(|| anyhow!("Employee not found"))
+}
+
+/// An optional date in ISO 8601 format.
+fn parse_date(value: &str) -> std::result::Result<Option<Date>, String> {
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        Date::parse(value, "%F")
+            .map(Some)
+            .map_err(|_| "A date like 2020-01-31".to_string())
+    }
+}
+
+fn noun(tab: Header) -> &'static str {
+    match tab {
+        Header::Skills => "Skill",
+        Header::Projects => "Project",
+        Header::Employees | Header::Matrix | Header::Timeline => "Employee",
+    }
+}
@@ -0,0 +1,627 @@
+use anyhow::{anyhow, Context as _, Result};
+use skill_manager_file::backend;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+use termion::event::Key;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Paragraph, Text, Widget},
+    Frame,
+};
+
+/// The name of the keymap file next to the CLI's config file.
+const KEYMAP_FILE: &str = "keymap.toml";
+/// The width of the keys in the help.
+const KEYS_WIDTH: usize = 18;
+
+/// Declares the actions with their name in the keymap file, their hint in the hint bar and
+/// their description in the help.
+macro_rules! actions {
+    ($($action:ident $name:literal $hint:expr, $description:literal;)*) => {
+        #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+        pub enum Action {
+            $($action,)*
+        }
+
+        impl Action {
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Action::$action),)*
+                    _ => None,
+                }
+            }
+
+            fn name(self) -> &'static str {
+                match self {
+                    $(Action::$action => $name,)*
+                }
+            }
+
+            /// The hint of the hint bar, actions with the same hint are shown together.
+            fn hint(self) -> Option<&'static str> {
+                match self {
+                    $(Action::$action => $hint,)*
+                }
+            }
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Action::$action => $description,)*
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    Help "help" Some("Help"), "Show the keys";
+    Employees "employees" None, "Open the employees";
+    Projects "projects" None, "Open the projects";
+    Skills "skills" None, "Open the skills";
+    Matrix "matrix" None, "Open the skill matrix";
+    Timeline "timeline" None, "Open the project timeline";
+    Down "down" Some("Select"), "Select the next entry";
+    Up "up" Some("Select"), "Select the previous entry";
+    PageDown "page_down" None, "Select the entry a page down";
+    PageUp "page_up" None, "Select the entry a page up";
+    First "first" None, "Select the first entry";
+    Last "last" None, "Select the last entry";
+    Filter "filter" Some("Filter"), "Filter the list, e.g. /ada or /rust>=3";
+    Messages "messages" Some("Messages"), "Show the messages";
+    New "new" Some("New"), "Create an entry";
+    Edit "edit" Some("Edit"), "Edit the selected entry";
+    Delete "delete" Some("Delete"), "Delete the selected entry";
+    Details "details" Some("Details"), "Change the skills and projects of the employee";
+    Save "save" Some("Save"), "Save the changes";
+    Quit "quit" Some("Quit"), "Clear the filter, or quit";
+    PreviousSkill "previous_skill" Some("Skill"), "Select the previous skill";
+    NextSkill "next_skill" Some("Skill"), "Select the next skill";
+    Sort "sort" Some("Sort"), "Sort by the selected skill, or by name again";
+    Earlier "earlier" Some("Scroll"), "Scroll back in time";
+    Later "later" Some("Scroll"), "Scroll forward in time";
+    Zoom "zoom" Some("Zoom"), "Switch between months and quarters";
+    NextProject "next_project" Some("Project"), "Show the next project, or all of them";
+    AssignSkill "assign_skill" Some("Assign skill"), "Assign a skill";
+    AssignProject "assign_project" Some("Assign project"), "Assign a project";
+    RaiseLevel "raise_level" Some("Level"), "Raise the level of the selected skill";
+    LowerLevel "lower_level" Some("Level"), "Lower the level of the selected skill";
+    ChangeSkill "change_skill" Some("Change"), "Change the selected skill";
+    Remove "remove" Some("Remove"), "Remove the selected skill or project";
+    Back "back" Some("Back"), "Go back";
+    NextField "next_field" Some("Next/previous field"), "Focus the next field";
+    PreviousField "previous_field" Some("Next/previous field"), "Focus the previous field";
+    Complete "complete" None, "Complete the skill or project";
+    Submit "submit" Some("Submit"), "Save the form";
+    Cancel "cancel" Some("Cancel"), "Discard the changes";
+    Apply "apply" Some("Apply"), "Keep the filter";
+    Clear "clear" Some("Clear"), "Clear the filter";
+    Yes "yes" Some("Yes"), "Yes";
+    No "no" Some("No"), "No";
+}
+
+/// Where keys are pressed, each context has its own bindings.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Context {
+    List,
+    Matrix,
+    Timeline,
+    Details,
+    Form,
+    Filter,
+    ConfirmQuit,
+    ConfirmDelete,
+    Log,
+    Help,
+}
+
+impl Context {
+    const ALL: [Context; 10] = [
+        Context::List,
+        Context::Matrix,
+        Context::Timeline,
+        Context::Details,
+        Context::Form,
+        Context::Filter,
+        Context::ConfirmQuit,
+        Context::ConfirmDelete,
+        Context::Log,
+        Context::Help,
+    ];
+
+    /// The name of the context's table in the keymap file.
+    fn name(self) -> &'static str {
+        match self {
+            Context::List => "list",
+            Context::Matrix => "matrix",
+            Context::Timeline => "timeline",
+            Context::Details => "details",
+            Context::Form => "form",
+            Context::Filter => "filter",
+            Context::ConfirmQuit => "confirm_quit",
+            Context::ConfirmDelete => "confirm_delete",
+            Context::Log => "log",
+            Context::Help => "help",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|context| context.name() == name)
+    }
+
+    /// The default bindings in the order of the hint bar.
+    fn defaults(self) -> Vec<(Action, Vec<&'static str>)> {
+        use Action::*;
+        let list = |specific: Vec<(Action, Vec<&'static str>)>| {
+            let mut bindings = vec![
+                (Help, vec!["?"]),
+                (New, vec!["+"]),
+                (Edit, vec!["enter"]),
+                (Delete, vec!["d", "delete"]),
+                (Filter, vec!["/"]),
+                (Down, vec!["j", "down"]),
+                (Up, vec!["k", "up"]),
+            ];
+            bindings.extend(specific);
+            bindings.extend(vec![
+                (Messages, vec!["m"]),
+                (Save, vec!["ctrl-s"]),
+                (Quit, vec!["esc"]),
+                (PageDown, vec!["pagedown"]),
+                (PageUp, vec!["pageup"]),
+                (First, vec!["g", "home"]),
+                (Last, vec!["G", "end"]),
+                (Employees, vec!["e"]),
+                (Projects, vec!["p"]),
+                (Skills, vec!["s"]),
+                (Matrix, vec!["x"]),
+                (Timeline, vec!["t"]),
+            ]);
+            bindings
+        };
+        match self {
+            Context::List => list(vec![(Details, vec!["l", "right"])]),
+            Context::Matrix => list(vec![
+                (PreviousSkill, vec!["h", "left"]),
+                (NextSkill, vec!["l", "right"]),
+                (Sort, vec!["o"]),
+            ]),
+            Context::Timeline => list(vec![
+                (Earlier, vec!["h", "left"]),
+                (Later, vec!["l", "right"]),
+                (Zoom, vec!["z"]),
+                (NextProject, vec!["f"]),
+            ]),
+            Context::Details => vec![
+                (Help, vec!["?"]),
+                (AssignSkill, vec!["s"]),
+                (AssignProject, vec!["p"]),
+                (RaiseLevel, vec!["+"]),
+                (LowerLevel, vec!["-"]),
+                (ChangeSkill, vec!["enter"]),
+                (Remove, vec!["d", "delete"]),
+                (Down, vec!["j", "down"]),
+                (Up, vec!["k", "up"]),
+                (Save, vec!["ctrl-s"]),
+                (Back, vec!["esc", "h", "left"]),
+            ],
+            Context::Form => vec![
+                (Help, vec!["f1"]),
+                (NextField, vec!["tab", "down"]),
+                (PreviousField, vec!["backtab", "up"]),
+                (Submit, vec!["enter"]),
+                (Cancel, vec!["esc"]),
+                (Complete, vec!["right"]),
+            ],
+            Context::Filter => vec![
+                (Apply, vec!["enter"]),
+                (Clear, vec!["esc"]),
+                (Help, vec!["f1"]),
+            ],
+            Context::ConfirmQuit => vec![
+                (Yes, vec!["y"]),
+                (No, vec!["n"]),
+                (Cancel, vec!["c", "esc"]),
+            ],
+            Context::ConfirmDelete => vec![(Yes, vec!["y"]), (No, vec!["n", "esc"])],
+            Context::Log => vec![(Help, vec!["?"]), (Back, vec!["esc", "m"])],
+            Context::Help => vec![(Back, vec!["esc", "?"])],
+        }
+    }
+}
+
+impl Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Context::List => "List",
+                Context::Matrix => "Matrix",
+                Context::Timeline => "Timeline",
+                Context::Details => "Details",
+                Context::Form => "Form",
+                Context::Filter => "Filter",
+                Context::ConfirmQuit => "Quit",
+                Context::ConfirmDelete => "Delete",
+                Context::Log => "Messages",
+                Context::Help => "Help",
+            }
+        )
+    }
+}
+
+/// The keys of the actions in each context.
+pub struct Keymap {
+    bindings: HashMap<Context, Vec<(Action, Vec<Key>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Context::ALL
+            .iter()
+            .map(|&context| {
+                let bindings = context
+                    .defaults()
+                    .into_iter()
+                    .map(|(action, keys)| {
+                        let keys = keys
+                            .into_iter()
+                            .map(|key| parse_key(key).expect("Invalid default key"))
+                            .collect();
+                        (action, keys)
+                    })
+                    .collect();
+                (context, bindings)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Reads a keymap file, by default the one next to the CLI's config file if it exists.
+    /// The file only lists the changed bindings, e.g. `quit = ["q", "esc"]` in its `[list]`
+    /// table.
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let default_path = backend::default_config_path()
+            .and_then(|config| config.parent().map(|dir| dir.join(KEYMAP_FILE)));
+        match (path, default_path) {
+            (Some(path), _) => Self::read(&path),
+            (None, Some(path)) if path.exists() => Self::read(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keymap file {}", path.display()))?;
+        let file: HashMap<String, HashMap<String, Vec<String>>> = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse keymap file {}", path.display()))?;
+        let mut keymap = Self::default();
+        for (context_name, actions) in file {
+            let context = Context::from_name(&context_name)
+                .ok_or_else(|| anyhow!("Unknown table [{}]", context_name))
+                .map_err(|e| in_file(e, path))?;
+            for (action_name, keys) in actions {
+                let action = Action::from_name(&action_name)
+                    .ok_or_else(|| anyhow!("Unknown action {}", action_name))
+                    .map_err(|e| in_file(e, path))?;
+                let keys = keys
+                    .iter()
+                    .map(|key| parse_key(key))
+                    .collect::<Result<Vec<_>>>()
+                    .map_err(|e| in_file(e, path))?;
+                keymap
+                    .bind(context, action, keys)
+                    .map_err(|e| in_file(e, path))?;
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// Replaces the keys of an action, taking them away from other actions of the context.
+    fn bind(&mut self, context: Context, action: Action, keys: Vec<Key>) -> Result<()> {
+        let bindings = self.bindings.entry(context).or_default();
+        if !bindings.iter().any(|(other, _)| *other == action) {
+            return Err(anyhow!(
+                "Action {} is not available in [{}]",
+                action.name(),
+                context.name()
+            ));
+        }
+        for (other, other_keys) in bindings.iter_mut() {
+            if *other == action {
+                *other_keys = keys.clone();
+            } else {
+                other_keys.retain(|key| !keys.contains(key));
+            }
+        }
+        Ok(())
+    }
+
+    /// The action of a key, if it is bound in the context.
+    pub fn action(&self, context: Context, key: Key) -> Option<Action> {
+        self.bindings(context)
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn bindings(&self, context: Context) -> &[(Action, Vec<Key>)] {
+        self.bindings
+            .get(&context)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The first key of an action, to show it.
+    pub fn key(&self, context: Context, action: Action) -> Option<Key> {
+        self.bindings(context)
+            .iter()
+            .find(|(other, _)| *other == action)
+            .and_then(|(_, keys)| keys.first().copied())
+    }
+
+    /// The hint bar of a context, e.g. `[j/k]Select [Esc]Quit`.
+    pub fn hints(&self, context: Context) -> String {
+        let mut hints: Vec<(Vec<String>, &str)> = vec![];
+        for (action, keys) in self.bindings(context) {
+            if let (Some(hint), Some(key)) = (action.hint(), keys.first()) {
+                match hints.last_mut() {
+                    Some((keys, last)) if *last == hint => keys.push(key_name(*key)),
+                    _ => hints.push((vec![key_name(*key)], hint)),
+                }
+            }
+        }
+        hints
+            .into_iter()
+            .map(|(keys, hint)| format!("[{}]{}", keys.join("/"), hint))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Renders the keys of a context over the middle of a chunk. Lines are padded with
+    /// spaces to hide what is below.
+    pub fn render_help(&self, context: Context, f: &mut Frame<impl Backend>, chunk: Rect) {
+        let bindings = self.bindings(context);
+        let width = chunk.width.min(70);
+        let height = chunk.height.min(bindings.len() as u16 + 2);
+        let area = Rect::new(
+            chunk.x + (chunk.width - width) / 2,
+            chunk.y + (chunk.height - height) / 2,
+            width,
+            height,
+        );
+        let inner = width.saturating_sub(2) as usize;
+        let mut lines = vec![];
+        for (action, keys) in bindings {
+            let keys: Vec<String> = keys.iter().map(|key| key_name(*key)).collect();
+            let keys = format!("{:<w$}", keys.join(", "), w = KEYS_WIDTH);
+            let description = format!(
+                "{:<w$}",
+                action.description(),
+                w = inner.saturating_sub(keys.chars().count())
+            );
+            lines.push(Text::styled(keys, Style::default().fg(Color::Yellow)));
+            lines.push(Text::raw(description));
+            lines.push(Text::raw("\n"));
+        }
+        Paragraph::new(lines.iter())
+            .block(
+                Block::default()
+                    .title(&format!("Keys: {}", context))
+                    .title_style(Style::default().modifier(Modifier::BOLD))
+                    .borders(Borders::ALL),
+            )
+            .render(f, area);
+    }
+}
+
+/// Names the keymap file in an error about its contents.
+fn in_file(error: anyhow::Error, path: &Path) -> anyhow::Error {
+    anyhow!("{} in keymap file {}", error, path.display())
+}
+
+/// Parses a key of the keymap file like `a`, `enter`, `ctrl-s` or `f1`.
+fn parse_key(key: &str) -> Result<Key> {
+    if let Some(c) = single_char(key) {
+        return Ok(Key::Char(c));
+    }
+    let lower = key.to_lowercase();
+    Ok(match lower.as_str() {
+        "enter" => Key::Char('\n'),
+        "tab" => Key::Char('\t'),
+        "space" => Key::Char(' '),
+        "backtab" => Key::BackTab,
+        "esc" => Key::Esc,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        _ => {
+            if let Some(c) = lower.strip_prefix("ctrl-").and_then(single_char) {
+                Key::Ctrl(c)
+            } else if let Some(c) = lower.strip_prefix("alt-").and_then(single_char) {
+                Key::Alt(c)
+            } else {
+                match lower.strip_prefix('f').map(str::parse) {
+                    Some(Ok(n)) if (1..=12).contains(&n) => Key::F(n),
+                    _ => return Err(anyhow!("Unknown key {}", key)),
+                }
+            }
+        }
+    })
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// The name of a key shown to the user.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char('\n') => "Enter".to_string(),
+        Key::Char('\t') => "Tab".to_string(),
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::BackTab => "Shift-Tab".to_string(),
+        Key::Esc => "Esc".to_string(),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Delete => "Del".to_string(),
+        Key::Insert => "Ins".to_string(),
+        Key::Left => "Left".to_string(),
+        Key::Right => "Right".to_string(),
+        Key::Up => "Up".to_string(),
+        Key::Down => "Down".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::PageUp => "PgUp".to_string(),
+        Key::PageDown => "PgDn".to_string(),
+        Key::Ctrl(c) => format!("Ctrl-{}", c),
+        Key::Alt(c) => format!("Alt-{}", c),
+        Key::F(n) => format!("F{}", n),
+        _ => "?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_key_test() -> Result<()> {
+        assert_eq!(parse_key("a")?, Key::Char('a'));
+        assert_eq!(parse_key("G")?, Key::Char('G'));
+        assert_eq!(parse_key("ä")?, Key::Char('ä'));
+        assert_eq!(parse_key("Enter")?, Key::Char('\n'));
+        assert_eq!(parse_key("space")?, Key::Char(' '));
+        assert_eq!(parse_key("backtab")?, Key::BackTab);
+        assert_eq!(parse_key("PageDown")?, Key::PageDown);
+        assert_eq!(parse_key("ctrl-s")?, Key::Ctrl('s'));
+        assert_eq!(parse_key("Ctrl-S")?, Key::Ctrl('s'));
+        assert_eq!(parse_key("alt-x")?, Key::Alt('x'));
+        assert_eq!(parse_key("f1")?, Key::F(1));
+        assert_eq!(parse_key("F12")?, Key::F(12));
+        for key in &[
+            "", "f0", "f13", "fx", "ctrl-", "ctrl-ab", "shift-a", "return",
+        ] {
+            assert!(parse_key(key).is_err(), "{} should be invalid", key);
+        }
+        assert_eq!(
+            parse_key("hyper-a").unwrap_err().to_string(),
+            "Unknown key hyper-a"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bind_takes_keys_from_other_actions_test() -> Result<()> {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.action(Context::List, Key::Char('d')),
+            Some(Action::Delete)
+        );
+
+        keymap.bind(
+            Context::List,
+            Action::Quit,
+            vec![Key::Char('d'), Key::Char('q')],
+        )?;
+
+        assert_eq!(
+            keymap.action(Context::List, Key::Char('d')),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(Context::List, Key::Char('q')),
+            Some(Action::Quit)
+        );
+        assert_eq!(keymap.action(Context::List, Key::Esc), None);
+        assert_eq!(keymap.key(Context::List, Action::Delete), Some(Key::Delete));
+        assert_eq!(
+            keymap.action(Context::Matrix, Key::Char('d')),
+            Some(Action::Delete)
+        );
+        assert_eq!(keymap.action(Context::Matrix, Key::Esc), Some(Action::Quit));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_action_test() -> Result<()> {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap
+                .bind(Context::Form, Action::Quit, vec![Key::Char('q')])
+                .unwrap_err()
+                .to_string(),
+            "Action quit is not available in [form]"
+        );
+        assert_eq!(keymap.action(Context::Form, Key::Char('q')), None);
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(KEYMAP_FILE);
+        fs::write(&path, "[list]\nfly = [\"f\"]\n")?;
+        assert_eq!(
+            Keymap::read(&path).err().unwrap().to_string(),
+            format!("Unknown action fly in keymap file {}", path.display())
+        );
+        fs::write(&path, "[lists]\nquit = [\"q\"]\n")?;
+        assert!(Keymap::read(&path)
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Unknown table [lists]"));
+        fs::write(&path, "[matrix]\nemployees = [\"E\"]\n")?;
+        let keymap = Keymap::read(&path)?;
+        assert_eq!(
+            keymap.key(Context::Matrix, Action::Employees),
+            Some(Key::Char('E'))
+        );
+        assert_eq!(
+            keymap.key(Context::List, Action::Employees),
+            Some(Key::Char('e'))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hints_test() -> Result<()> {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.hints(Context::List),
+            "[?]Help [+]New [Enter]Edit [d]Delete [/]Filter [j/k]Select [l]Details \
+             [m]Messages [Ctrl-s]Save [Esc]Quit"
+        );
+        assert_eq!(keymap.hints(Context::ConfirmDelete), "[y]Yes [n]No");
+
+        keymap.bind(Context::List, Action::Down, vec![Key::Char('n')])?;
+        keymap.bind(Context::List, Action::Help, vec![Key::Char('+'), Key::F(1)])?;
+
+        assert_eq!(
+            keymap.hints(Context::List),
+            "[+]Help [Enter]Edit [d]Delete [/]Filter [n/k]Select [l]Details [m]Messages \
+             [Ctrl-s]Save [Esc]Quit"
+        );
+        Ok(())
+    }
+}
@@ -1,39 +1,60 @@
-use anyhow::anyhow;
-use skill_manager::{
-    employees::{
-        usecase::{AddEmployee, AddEmployeeRequest, FindEmployees},
-        EmailAddress, FirstName, LastName, TelephoneNumber, Title,
-    },
-    projects::{
-        usecase::{AddProject, FindProjects},
-        ProjectDescription, ProjectLabel,
-    },
-    skills::{
-        usecase::{AddSkill, FindSkills},
-        SkillLabel,
-    },
-};
-use skill_manager_in_memory::{employees::EmployeeDb, projects::ProjectDb, skills::SkillDb};
+use assignments::Assignment;
+use config::ConfigArgs;
+use entries::Entry;
+use filter::Filter;
+use form::Form;
+use keymap::{Action, Context, Keymap};
+use matrix::Matrix;
+use messages::Messages;
+use skill_manager::{employees::Employee, projects::ProjectLabel, skills::SkillLabel};
+use skill_manager_file::unit_of_work::UnitOfWork;
 use std::{
     fmt::{self, Display},
-    io,
+    io, mem,
+    path::PathBuf,
 };
+use structopt::StructOpt;
 use termion::{
     event::Key,
     input::{MouseTerminal, TermRead},
     raw::IntoRawMode,
     screen::AlternateScreen,
 };
+use timeline::{Timeline, Zoom};
 use tui::{
     backend::{Backend, TermionBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Paragraph, Tabs, Text, Widget},
+    widgets::{Block, Borders, Paragraph, Tabs, Text, Widget},
     Frame, Terminal,
 };
 
+mod assignments;
+mod config;
+mod entries;
+mod filter;
+mod form;
+mod keymap;
+mod matrix;
+mod messages;
+mod timeline;
+
+#[derive(StructOpt)]
+struct Opt {
+    #[structopt(flatten)]
+    config: ConfigArgs,
+    /// Shows the skills that are marked as secret in the matrix
+    #[structopt(long = "include-secret")]
+    include_secret: bool,
+    /// The keymap file, by default keymap.toml next to the config file
+    #[structopt(long = "keymap", parse(from_os_str))]
+    keymap: Option<PathBuf>,
+}
+
 fn main() {
-    if let Err(e) = run() {
+    let opt = Opt::from_args();
+
+    if let Err(e) = run(opt) {
         eprintln!("{}", e);
     }
 }
@@ -45,23 +66,83 @@ enum Header {
     Skills,
     Projects,
     Employees,
+    /// The skills of all employees at a glance.
+    Matrix,
+    /// The project assignments of all employees over time.
+    Timeline,
 }
 
 impl Header {
     const SIZE: u16 = 3;
 
-    fn new(selected_tab: &Header) -> Tabs<&'static str> {
+    const ALL: [Header; 5] = [
+        Header::Employees,
+        Header::Projects,
+        Header::Skills,
+        Header::Matrix,
+        Header::Timeline,
+    ];
+
+    fn tabs<'a>(
+        selected_tab: &Header,
+        has_changes: bool,
+        titles: &'a [String],
+    ) -> Tabs<'a, String> {
+        let title = if has_changes {
+            "Skill Manager (unsaved changes)"
+        } else {
+            "Skill Manager"
+        };
         Tabs::default()
-            .block(Block::default().title("Skill Manager"))
-            .titles(&["[E]mployees", "[P]rojects", "[S]kills"])
+            .block(Block::default().title(title))
+            .titles(titles)
             .select(selected_tab.ix())
             .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
     }
+
+    /// The titles of the tabs with the keys opening them in the context of the open tab, e.g.
+    /// `[E]mployees` or `Matri[x]`.
+    fn titles(keymap: &Keymap, context: Context) -> Vec<String> {
+        Header::ALL
+            .iter()
+            .map(|tab| {
+                let title = tab.to_string();
+                let key = match keymap.key(context, tab.action()) {
+                    Some(Key::Char(c)) => c,
+                    Some(key) => return format!("{} [{}]", title, keymap::key_name(key)),
+                    None => return title,
+                };
+                match title.to_lowercase().find(key.to_ascii_lowercase()) {
+                    Some(ix) if key.is_ascii_alphabetic() => format!(
+                        "{}[{}]{}",
+                        &title[..ix],
+                        &title[ix..ix + 1],
+                        &title[ix + 1..]
+                    ),
+                    _ => format!("{} [{}]", title, key),
+                }
+            })
+            .collect()
+    }
+
+    /// The action opening the tab.
+    fn action(self) -> Action {
+        match self {
+            Header::Skills => Action::Skills,
+            Header::Projects => Action::Projects,
+            Header::Employees => Action::Employees,
+            Header::Matrix => Action::Matrix,
+            Header::Timeline => Action::Timeline,
+        }
+    }
+
     fn ix(&self) -> usize {
         match self {
             Header::Skills => 2,
             Header::Projects => 1,
             Header::Employees => 0,
+            Header::Matrix => 3,
+            Header::Timeline => 4,
         }
     }
 }
@@ -75,6 +156,8 @@ impl Display for Header {
                 Header::Employees => "Employees",
                 Header::Projects => "Projects",
                 Header::Skills => "Skills",
+                Header::Matrix => "Matrix",
+                Header::Timeline => "Timeline",
             }
         )
     }
@@ -82,56 +165,149 @@ impl Display for Header {
 
 struct List {
     title: String,
-    data: Vec<String>,
+    /// The labels with the indices of the characters matching the filter.
+    data: Vec<(String, Vec<usize>)>,
+    selected: Option<usize>,
 }
 
 impl List {
-    fn new(data: Vec<String>, state: &State) -> Self {
+    fn new(entries: &[Entry], total: usize, state: &State) -> Self {
+        let filter = Filter::parse(&state.filter);
+        let title = if filter.is_empty() {
+            state.open_tab.to_string()
+        } else {
+            format!(
+                "{} /{} ({} of {})",
+                state.open_tab,
+                state.filter.trim(),
+                entries.len(),
+                total
+            )
+        };
         Self {
-            data,
-            title: state.open_tab.to_string(),
+            data: entries
+                .iter()
+                .map(|entry| {
+                    let label = entry.label();
+                    let highlights = filter.highlights(&label);
+                    (label, highlights)
+                })
+                .collect(),
+            title,
+            selected: state.selected(entries.len()),
         }
     }
-    fn render<'a>(&self, mut f: &mut Frame<impl Backend>, chunk: Rect) {
-        let list: Vec<_> = self.data.iter().map(Text::raw).collect();
-        Paragraph::new(list.iter())
+
+    /// Renders the list like a `SelectableList`, scrolled to the selected entry, with the
+    /// characters matching the filter underlined.
+    fn render(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        // The title takes one line.
+        let height = chunk.height.saturating_sub(1).max(1) as usize;
+        let offset = match self.selected {
+            Some(selected) if selected >= height => selected + 1 - height,
+            _ => 0,
+        };
+        let mut lines = vec![];
+        for (ix, (label, highlights)) in self.data.iter().enumerate().skip(offset).take(height) {
+            let (symbol, style) = if Some(ix) == self.selected {
+                (
+                    "> ",
+                    Style::default().fg(Color::Yellow).modifier(Modifier::BOLD),
+                )
+            } else {
+                ("  ", Style::default())
+            };
+            lines.push(Text::styled(symbol, style));
+            let mut run = String::new();
+            let mut highlighted = false;
+            for (ix, c) in label.chars().enumerate() {
+                if highlights.contains(&ix) != highlighted && !run.is_empty() {
+                    lines.push(Text::styled(run.clone(), highlight(style, highlighted)));
+                    run.clear();
+                }
+                highlighted = highlights.contains(&ix);
+                run.push(c);
+            }
+            lines.push(Text::styled(run, highlight(style, highlighted)));
+            lines.push(Text::raw("\n"));
+        }
+        Paragraph::new(lines.iter())
             .block(Block::default().title(&self.title))
-            .alignment(Alignment::Left)
-            .wrap(false)
-            .render(&mut f, chunk);
+            .render(f, chunk);
     }
 }
 
-struct InputField<'a> {
-    input: &'a str,
-    open_tab: Header,
+/// The left pane of a tab: the list of its entries, or the matrix of the employees' skills.
+enum Overview {
+    List(List),
+    Matrix(Matrix),
+    Timeline(Timeline),
 }
 
-impl<'a> InputField<'a> {
-    const SIZE: u16 = 1;
-    fn new(input: &'a str, open_tab: Header) -> Self {
-        Self { input, open_tab }
+impl Overview {
+    fn render(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        match self {
+            Overview::List(list) => list.render(f, chunk),
+            Overview::Matrix(matrix) => matrix.render(f, chunk),
+            Overview::Timeline(timeline) => timeline.render(f, chunk),
+        }
+    }
+}
+
+fn highlight(style: Style, highlighted: bool) -> Style {
+    if highlighted {
+        style.modifier(style.modifier | Modifier::UNDERLINED)
+    } else {
+        style
     }
+}
 
-    fn render(&mut self, mut f: &mut Frame<impl Backend>, chunk: Rect) -> (u16, u16) {
-        let paragraph_chunk = chunk;
-        let prefix = match &self.open_tab {
-            Header::Skills => "New Skill: ",
-            Header::Projects => "New Project: ",
-            Header::Employees => "New Employee: ",
+struct DetailPane {
+    title: &'static str,
+    lines: Vec<Text<'static>>,
+    focused: bool,
+}
+
+impl DetailPane {
+    fn new(entries: &[Entry], state: &State, db: &UnitOfWork) -> Result<Self> {
+        let focus = match state.shown_mode() {
+            InputMode::Details => Some(state.assignment),
+            _ => None,
+        };
+        if let InputMode::ConfirmDelete(entry) = state.shown_mode() {
+            return Ok(Self {
+                title: "Delete",
+                lines: entry.dependents(db)?,
+                focused: true,
+            });
+        }
+        let lines = match state.selected(entries.len()) {
+            Some(selected) => entries[selected].details(db, focus)?,
+            None => vec![],
         };
-        Paragraph::new([Text::raw(prefix), Text::raw(self.input)].iter())
-            .style(Style::default())
+        Ok(Self {
+            title: "Details",
+            lines,
+            focused: focus.is_some(),
+        })
+    }
+
+    fn render(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        let title_style = if self.focused {
+            Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        Paragraph::new(self.lines.iter())
+            .block(
+                Block::default()
+                    .title(self.title)
+                    .title_style(title_style)
+                    .borders(Borders::LEFT),
+            )
             .alignment(Alignment::Left)
-            .render(&mut f, chunk);
-
-        (
-            //TODO overflow alert!
-            paragraph_chunk.left()
-                + prefix.chars().count() as u16
-                + self.input.chars().count() as u16,
-            paragraph_chunk.top(),
-        )
+            .wrap(true)
+            .render(f, chunk);
     }
 }
 
@@ -143,12 +319,44 @@ impl HotkeyHelp {
 
 enum InputMode {
     List,
-    Input(String),
+    /// The detail pane of an employee has the focus to change its assignments.
+    Details,
+    Form(Form),
+    ConfirmQuit,
+    /// Typing the filter of the list.
+    Filter,
+    /// Shows the message log instead of the details.
+    Log,
+    /// Asks whether to delete the entry, showing the data depending on it.
+    ConfirmDelete(Entry),
+    /// Shows the keys of the mode below.
+    Help(Box<InputMode>),
 }
 
 struct State {
     open_tab: Header,
     mode: InputMode,
+    /// The index of the selected entry in the open tab's list.
+    selection: usize,
+    /// The filter of the open tab's list, see [`Filter`].
+    filter: String,
+    /// The index of the selected assignment in the detail pane of an employee.
+    assignment: usize,
+    /// The number of entries that fit into the list, the distance of page up and down.
+    page_size: usize,
+    messages: Messages,
+    /// The index of the selected skill column of the matrix.
+    column: usize,
+    /// The skill the matrix is sorted by, by name if none.
+    sort: Option<SkillLabel>,
+    /// Whether the matrix shows the levels of secret skills.
+    include_secret: bool,
+    zoom: Zoom,
+    /// The number of periods the timeline is scrolled back from its last period.
+    period: usize,
+    /// The project the timeline is filtered by, all projects if none.
+    project: Option<ProjectLabel>,
+    keymap: Keymap,
 }
 
 impl Default for State {
@@ -156,40 +364,293 @@ impl Default for State {
         Self {
             open_tab: Header::Employees,
             mode: InputMode::List,
+            selection: 0,
+            filter: String::new(),
+            assignment: 0,
+            page_size: 1,
+            messages: Messages::default(),
+            column: 0,
+            sort: None,
+            include_secret: false,
+            zoom: Zoom::Month,
+            period: 0,
+            project: None,
+            keymap: Keymap::default(),
         }
     }
 }
 
 impl State {
-    fn handle_input(&mut self, key: Key) -> Result<Vec<Effect>> {
+    /// The entries of the open tab's list matching the filter, sorted like the matrix or
+    /// filtered by the project of the timeline if one of them is open.
+    fn entries(&self, db: &UnitOfWork) -> Result<Vec<Entry>> {
+        let filter = Filter::parse(&self.filter);
+        let mut entries: Vec<Entry> = entries::load(self.open_tab, db)?
+            .into_iter()
+            .filter(|entry| filter.matches(entry))
+            .collect();
+        if let (Header::Matrix, Some(skill)) = (self.open_tab, &self.sort) {
+            matrix::sort(&mut entries, skill, self.include_secret);
+        }
+        if let (Header::Timeline, Some(project)) = (self.open_tab, &self.project) {
+            entries.retain(|entry| match entry {
+                Entry::Employee(employee) => timeline::assignments(employee, Some(project))
+                    .next()
+                    .is_some(),
+                _ => true,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// The selected entry of a list with `len` entries.
+    fn selected(&self, len: usize) -> Option<usize> {
+        clamp(self.selection, len)
+    }
+
+    /// The selected employee and its selected assignment, if any.
+    fn selected_assignment(
+        &self,
+        db: &UnitOfWork,
+    ) -> Result<Option<(Employee, Option<Assignment>)>> {
+        let entries = self.entries(db)?;
+        Ok(match self.selected(entries.len()).map(|ix| &entries[ix]) {
+            Some(Entry::Employee(employee)) => {
+                let assignments = assignments::of(employee);
+                let assignment =
+                    clamp(self.assignment, assignments.len()).map(|ix| assignments[ix].clone());
+                Some((employee.clone(), assignment))
+            }
+            _ => None,
+        })
+    }
+
+    fn open(&mut self, tab: Header) {
+        if tab.ix() != self.open_tab.ix() {
+            self.open_tab = tab;
+            self.selection = 0;
+            self.filter.clear();
+        }
+    }
+
+    /// Selects an entry of the open tab, e.g. after it was added or renamed.
+    fn select(&mut self, entry: &Entry, db: &UnitOfWork) -> Result<()> {
+        if let Some(ix) = self
+            .entries(db)?
+            .iter()
+            .position(|other| other.is_same(entry))
+        {
+            self.selection = ix;
+        }
+        Ok(())
+    }
+
+    /// Moves the selection by `distance` entries, staying within the list.
+    fn move_selection(&mut self, distance: isize, len: usize) {
+        self.selection = moved(self.selection, distance, len);
+    }
+
+    /// Sorts the matrix by the selected skill, or by name again if it is sorted by it already.
+    /// The selected employee stays selected.
+    fn toggle_sort(&mut self, db: &UnitOfWork) -> Result<()> {
+        let selected = self.entries(db)?.get(self.selection).cloned();
+        let skills = matrix::skills(db)?;
+        let skill = clamp(self.column, skills.len()).map(|ix| skills[ix].clone());
+        self.sort = if self.sort == skill { None } else { skill };
+        match selected {
+            Some(entry) => self.select(&entry, db),
+            None => Ok(()),
+        }
+    }
+
+    /// The mode whose panes are shown, the one below the help if it is open.
+    fn shown_mode(&self) -> &InputMode {
+        match &self.mode {
+            InputMode::Help(previous) => previous,
+            mode => mode,
+        }
+    }
+
+    /// The context of the keys in the mode.
+    fn context(&self, mode: &InputMode) -> Context {
+        match mode {
+            InputMode::List => match self.open_tab {
+                Header::Matrix => Context::Matrix,
+                Header::Timeline => Context::Timeline,
+                _ => Context::List,
+            },
+            InputMode::Details => Context::Details,
+            InputMode::Form(_) => Context::Form,
+            InputMode::ConfirmQuit => Context::ConfirmQuit,
+            InputMode::Filter => Context::Filter,
+            InputMode::Log => Context::Log,
+            InputMode::ConfirmDelete(_) => Context::ConfirmDelete,
+            InputMode::Help(_) => Context::Help,
+        }
+    }
+
+    fn handle_input(&mut self, key: Key, db: &UnitOfWork) -> Result<Vec<Effect>> {
+        let entries = self.entries(db)?;
+        let len = entries.len();
+        let action = self.keymap.action(self.context(&self.mode), key);
         let mut effects = vec![];
+        if action == Some(Action::Help) {
+            let previous = mem::replace(&mut self.mode, InputMode::List);
+            self.mode = InputMode::Help(Box::new(previous));
+            return Ok(effects);
+        }
         match &mut self.mode {
-            InputMode::Input(input) => match key {
-                Key::Esc => self.mode = InputMode::List,
-                Key::Backspace => {
-                    input.pop();
+            InputMode::Help(previous) => {
+                if action == Some(Action::Back) {
+                    self.mode = mem::replace(previous, InputMode::List);
                 }
-                Key::Char('\n') => {
-                    effects.push(Effect::SendInput(input.clone()));
+            }
+            InputMode::ConfirmDelete(entry) => match action {
+                Some(Action::Yes) => {
+                    effects.push(Effect::Delete(Box::new(entry.clone())));
                     self.mode = InputMode::List;
                 }
-                Key::Char(c) => {
-                    input.push(c);
+                Some(Action::No) => self.mode = InputMode::List,
+                _ => {}
+            },
+            InputMode::Log => {
+                if action == Some(Action::Back) {
+                    self.mode = InputMode::List;
+                }
+            }
+            InputMode::Filter => match (action, key) {
+                (Some(Action::Apply), _) => self.mode = InputMode::List,
+                (Some(Action::Clear), _) => {
+                    self.filter.clear();
+                    self.mode = InputMode::List;
+                }
+                (_, Key::Backspace) => {
+                    self.filter.pop();
+                    self.selection = 0;
+                }
+                (_, Key::Char(c)) => {
+                    self.filter.push(c);
+                    self.selection = 0;
+                }
+                _ => {}
+            },
+            InputMode::ConfirmQuit => match action {
+                Some(Action::Yes) => effects.extend(vec![Effect::Save, Effect::Quit]),
+                Some(Action::No) => effects.push(Effect::Quit),
+                Some(Action::Cancel) => self.mode = InputMode::List,
+                _ => {}
+            },
+            InputMode::Details => match self.selected_assignment(db)? {
+                None => self.mode = InputMode::List,
+                Some((employee, assignment)) => match action {
+                    Some(Action::Down) => {
+                        let len = assignments::of(&employee).len();
+                        self.assignment = moved(self.assignment, 1, len);
+                    }
+                    Some(Action::Up) => {
+                        let len = assignments::of(&employee).len();
+                        self.assignment = moved(self.assignment, -1, len);
+                    }
+                    Some(Action::AssignSkill) => {
+                        self.mode = InputMode::Form(Form::assign_skill(employee, None, db)?)
+                    }
+                    Some(Action::AssignProject) => {
+                        self.mode = InputMode::Form(Form::assign_project(employee, db)?)
+                    }
+                    Some(Action::ChangeSkill) => {
+                        if let Some(Assignment::Skill(skill)) = assignment {
+                            self.mode =
+                                InputMode::Form(Form::assign_skill(employee, Some(skill), db)?);
+                        }
+                    }
+                    Some(Action::RaiseLevel) => effects.push(Effect::ChangeLevel(1)),
+                    Some(Action::LowerLevel) => effects.push(Effect::ChangeLevel(-1)),
+                    Some(Action::Remove) => effects.push(Effect::Unassign),
+                    Some(Action::Save) => effects.push(Effect::Save),
+                    Some(Action::Back) => self.mode = InputMode::List,
+                    _ => {}
+                },
+            },
+            InputMode::Form(form) => match (action, key) {
+                (Some(Action::Cancel), _) if form.assigns() => self.mode = InputMode::Details,
+                (Some(Action::Cancel), _) => self.mode = InputMode::List,
+                (Some(Action::Submit), _) => effects.push(Effect::SubmitForm),
+                (Some(Action::Complete), _) => form.complete(),
+                (Some(Action::NextField), _) => form.focus_next(),
+                (Some(Action::PreviousField), _) => form.focus_previous(),
+                (_, Key::Backspace) => {
+                    form.pop();
+                    effects.push(Effect::ValidateForm);
+                }
+                (_, Key::Char(c)) => {
+                    form.push(c);
+                    effects.push(Effect::ValidateForm);
                 }
                 _ => {}
             },
-            InputMode::List => match key {
-                Key::Char('p') => {
-                    self.open_tab = Header::Projects;
+            InputMode::List => match action {
+                Some(Action::Projects) => self.open(Header::Projects),
+                Some(Action::Skills) => self.open(Header::Skills),
+                Some(Action::Employees) => self.open(Header::Employees),
+                Some(Action::Matrix) => self.open(Header::Matrix),
+                Some(Action::Timeline) => self.open(Header::Timeline),
+                Some(Action::PreviousSkill) => {
+                    self.column = moved(self.column, -1, matrix::skills(db)?.len())
                 }
-                Key::Char('s') => {
-                    self.open_tab = Header::Skills;
+                Some(Action::NextSkill) => {
+                    self.column = moved(self.column, 1, matrix::skills(db)?.len())
+                }
+                Some(Action::Sort) => self.toggle_sort(db)?,
+                Some(Action::Earlier) => {
+                    let len = timeline::len(&entries, self.project.as_ref(), self.zoom);
+                    self.period = moved(self.period, 1, len)
+                }
+                Some(Action::Later) => {
+                    let len = timeline::len(&entries, self.project.as_ref(), self.zoom);
+                    self.period = moved(self.period, -1, len)
+                }
+                Some(Action::Zoom) => {
+                    let (zoom, period) = self.zoom.toggle(self.period);
+                    self.zoom = zoom;
+                    self.period = period;
+                }
+                Some(Action::NextProject) => {
+                    self.project = timeline::next_project(db, self.project.as_ref())?;
+                    self.selection = 0;
+                    self.period = 0;
+                }
+                Some(Action::Down) => self.move_selection(1, len),
+                Some(Action::Up) => self.move_selection(-1, len),
+                Some(Action::PageDown) => self.move_selection(self.page_size as isize, len),
+                Some(Action::PageUp) => self.move_selection(-(self.page_size as isize), len),
+                Some(Action::First) => self.move_selection(isize::MIN / 2, len),
+                Some(Action::Last) => self.move_selection(isize::MAX / 2, len),
+                Some(Action::Filter) => self.mode = InputMode::Filter,
+                Some(Action::Messages) => self.mode = InputMode::Log,
+                Some(Action::New) => self.mode = InputMode::Form(Form::new(self.open_tab)),
+                Some(Action::Edit) => {
+                    if let Some(selected) = self.selected(len) {
+                        self.mode = InputMode::Form(Form::edit(entries[selected].clone()));
+                    }
+                }
+                Some(Action::Delete) => {
+                    if let Some(selected) = self.selected(len) {
+                        self.mode = InputMode::ConfirmDelete(entries[selected].clone());
+                    }
+                }
+                Some(Action::Details) => {
+                    if self.selected_assignment(db)?.is_some() {
+                        self.mode = InputMode::Details;
+                        self.assignment = 0;
+                    }
                 }
-                Key::Char('e') => {
-                    self.open_tab = Header::Employees;
+                Some(Action::Save) => effects.push(Effect::Save),
+                Some(Action::Quit) if !self.filter.is_empty() => {
+                    self.filter.clear();
+                    self.selection = 0;
                 }
-                Key::Char('+') => self.mode = InputMode::Input(String::new()),
-                Key::Esc => effects.push(Effect::Quit),
+                Some(Action::Quit) if db.has_changes() => self.mode = InputMode::ConfirmQuit,
+                Some(Action::Quit) => effects.push(Effect::Quit),
                 _ => {}
             },
         }
@@ -198,18 +659,23 @@ impl State {
 }
 
 enum Effect {
-    SendInput(String),
+    /// Validates the open form again after a change.
+    ValidateForm,
+    SubmitForm,
+    /// Raises or lowers the level of the selected skill of an employee.
+    ChangeLevel(isize),
+    /// Removes the selected skill or project from an employee.
+    Unassign,
+    Delete(Box<Entry>),
+    Save,
     Quit,
 }
 
-#[derive(Default)]
-struct Db {
-    skills: SkillDb,
-    projects: ProjectDb,
-    employees: EmployeeDb,
-}
+/// Runs the TUI on the data the CLI uses, keeping the data directory locked until it quits.
+fn run(opt: Opt) -> Result<()> {
+    let mut db = config::open_unit_of_work(opt.config)?;
+    let keymap = Keymap::load(opt.keymap)?;
 
-fn run() -> Result<()> {
     let stdout = io::stdout().into_raw_mode()?;
     let stdout = MouseTerminal::from(stdout);
     let stdout = AlternateScreen::from(stdout);
@@ -219,106 +685,251 @@ fn run() -> Result<()> {
     // terminal.clear()?;
     terminal.hide_cursor()?;
 
-    let mut state = State::default();
-    let mut db = Db::default();
-    draw(&mut terminal, &state, &db)?;
+    let mut state = State {
+        include_secret: opt.include_secret,
+        keymap,
+        ..State::default()
+    };
+    for recovered in db.recovered_backups() {
+        state.messages.info(format!("Warning: {}", recovered));
+    }
+    draw(&mut terminal, &mut state, &db)?;
 
     for k in stdin.keys() {
-        let effects = state.handle_input(k?)?;
-        for effect in effects {
-            match effect {
-                Effect::Quit => return Ok(()),
-                Effect::SendInput(input) => match state.open_tab {
-                    Header::Skills => {
-                        db.skills.add(SkillLabel(input))?;
-                    }
-                    Header::Employees => {
-                        create_employee(&mut db, input)?;
+        state.page_size = page_size(terminal.size()?);
+        state.messages.dismiss();
+        match update(&mut state, k?, &mut db) {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(e) => state.messages.error(&e),
+        }
+        draw(&mut terminal, &mut state, &db)?;
+    }
+
+    Ok(())
+}
+
+/// Handles a key, returns whether to quit. Errors are shown to the user instead of ending
+/// the application.
+fn update(state: &mut State, key: Key, db: &mut UnitOfWork) -> Result<bool> {
+    for effect in state.handle_input(key, db)? {
+        match effect {
+            Effect::Quit => return Ok(true),
+            Effect::Save => {
+                db.save()?;
+                state.messages.info("Saved");
+            }
+            Effect::ValidateForm => {
+                if let InputMode::Form(form) = &mut state.mode {
+                    form.revalidate(db)?;
+                }
+            }
+            Effect::SubmitForm => {
+                if let InputMode::Form(form) = &mut state.mode {
+                    if let Some(saved) = form.submit(db)? {
+                        state.mode = if form.assigns() {
+                            InputMode::Details
+                        } else {
+                            InputMode::List
+                        };
+                        state.select(&saved, db)?;
+                        state.messages.info(format!("Saved {}", saved.label()));
                     }
-                    Header::Projects => {
-                        db.projects
-                            .add(ProjectLabel(input), ProjectDescription("".into()))?;
+                }
+            }
+            Effect::ChangeLevel(distance) => {
+                if let Some((employee, Some(Assignment::Skill(skill)))) =
+                    state.selected_assignment(db)?
+                {
+                    assignments::change_level(db, &employee, &skill, distance)?;
+                    // Skills are sorted by level, so the selection follows the skill.
+                    if let Some((employee, _)) = state.selected_assignment(db)? {
+                        if let Some(ix) =
+                            assignments::of(&employee).iter().position(
+                                |assignment| match assignment {
+                                    Assignment::Skill(other) => other.label == skill.label,
+                                    Assignment::Project(_) => false,
+                                },
+                            )
+                        {
+                            state.assignment = ix;
+                        }
                     }
-                },
+                }
+            }
+            Effect::Delete(entry) => {
+                entry.delete(db)?;
+                state.messages.info(format!("Deleted {}", entry.label()));
+            }
+            Effect::Unassign => {
+                if let Some((employee, Some(assignment))) = state.selected_assignment(db)? {
+                    assignments::unassign(db, &employee, &assignment)?;
+                    let label = match assignment {
+                        Assignment::Skill(skill) => skill.label.0,
+                        Assignment::Project(project) => project.label.0,
+                    };
+                    state.messages.info(format!(
+                        "Removed {} from {} {}",
+                        label, employee.first_name, employee.last_name
+                    ));
+                }
             }
         }
-        draw(&mut terminal, &state, &db)?;
     }
+    Ok(false)
+}
 
-    Ok(())
+/// The index of the selection in a list with `len` entries, the last one if the list got
+/// shorter.
+fn clamp(selection: usize, len: usize) -> Option<usize> {
+    if len == 0 {
+        None
+    } else {
+        Some(selection.min(len - 1))
+    }
 }
 
-fn create_employee(db: &mut Db, input: String) -> Result<()> {
-    let mut words = input.split_whitespace();
-    let first_name = words.next().ok_or(anyhow!("Empty first name"))?;
-    let last_name = LastName(words.collect());
-    db.employees.add(AddEmployeeRequest {
-        first_name: FirstName(first_name.into()),
-        last_name,
-        title: Title(String::new()),
-        email: EmailAddress(String::new()),
-        telephone: TelephoneNumber(String::new()),
-    })?;
-    Ok(())
+/// A selection moved by `distance` entries, staying within a list with `len` entries.
+fn moved(selection: usize, distance: isize, len: usize) -> usize {
+    let current = clamp(selection, len).unwrap_or(0) as isize;
+    let last = len.saturating_sub(1) as isize;
+    (current + distance).max(0).min(last) as usize
+}
+
+/// The number of list entries that fit on the screen.
+fn page_size(size: Rect) -> usize {
+    // The list's title takes one line.
+    size.height
+        .saturating_sub(Header::SIZE + Messages::SIZE + HotkeyHelp::SIZE + 1)
+        .max(1) as usize
+}
+
+/// The list or matrix and the detail pane of the open tab.
+fn panes(state: &State, db: &UnitOfWork) -> Result<(Overview, DetailPane)> {
+    let entries = state.entries(db)?;
+    let overview = match state.open_tab {
+        Header::Matrix => {
+            let skills = matrix::skills(db)?;
+            let column = clamp(state.column, skills.len());
+            Overview::Matrix(Matrix::new(
+                &entries,
+                skills,
+                state.selected(entries.len()),
+                column,
+                state.sort.as_ref(),
+                state.include_secret,
+            ))
+        }
+        Header::Timeline => Overview::Timeline(Timeline::new(
+            &entries,
+            state.selected(entries.len()),
+            state.period,
+            state.project.as_ref(),
+            state.zoom,
+        )),
+        _ => {
+            let total = entries::load(state.open_tab, db)?.len();
+            Overview::List(List::new(&entries, total, state))
+        }
+    };
+    Ok((overview, DetailPane::new(&entries, state, db)?))
 }
 
-fn draw(terminal: &mut Terminal<impl Backend>, state: &State, db: &Db) -> Result<()> {
+/// Draws the screen. Failing to load the data leaves the panes empty and shows the error.
+fn draw(terminal: &mut Terminal<impl Backend>, state: &mut State, db: &UnitOfWork) -> Result<()> {
+    let (overview, detail_pane) = match panes(state, db) {
+        Ok(panes) => panes,
+        Err(e) => {
+            state.messages.error(&e);
+            (
+                Overview::List(List::new(&[], 0, state)),
+                DetailPane {
+                    title: "Details",
+                    lines: vec![],
+                    focused: false,
+                },
+            )
+        }
+    };
+    let state = &*state;
     let mut set_cursor = None;
     terminal.draw(|mut f| {
         let size = f.size();
 
-        let layout = match &state.mode {
-            InputMode::List => vec![
-                Constraint::Length(Header::SIZE),
-                Constraint::Min(20),
-                Constraint::Length(HotkeyHelp::SIZE),
-            ],
-            InputMode::Input(_) => vec![
-                Constraint::Length(Header::SIZE),
-                Constraint::Min(20),
-                Constraint::Length(InputField::SIZE),
-                Constraint::Length(HotkeyHelp::SIZE),
-            ],
-        };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(0)
-            .constraints(layout)
+            .constraints(vec![
+                Constraint::Length(Header::SIZE),
+                Constraint::Min(20),
+                Constraint::Length(Messages::SIZE),
+                Constraint::Length(HotkeyHelp::SIZE),
+            ])
             .split(size);
 
-        let mut tabs = Header::new(&state.open_tab);
+        let titles = Header::titles(&state.keymap, state.context(&InputMode::List));
+        let mut tabs = Header::tabs(&state.open_tab, db.has_changes(), &titles);
         tabs.render(&mut f, chunks[0]);
 
-        let list = List::new(retrieve_data(&state, &db), &state);
-        list.render(&mut f, chunks[1]);
-
-        if let InputMode::Input(i) = &state.mode {
-            let mut input_field = InputField::new(i, state.open_tab);
-            let cursor_pos = input_field.render(&mut f, chunks[2]);
-            set_cursor = Some(cursor_pos);
-
-            //let paragraph_chunk = chunks[2];
-            //Paragraph::new(iter::once(&Text::raw(i)))
-            //    .block(Block::default().title("Input"))
-            //    .style(Style::default())
-            //    .alignment(Alignment::Left)
-            //    .wrap(true)
-            //    .render(&mut f, chunks[2]);
-
-            //set_cursor = Some((
-            //    //TODO overflow alert!
-            //    paragraph_chunk.left() + i.len() as u16 + 1,
-            //    paragraph_chunk.top() + 1,
-            //));
+        let help = matches!(state.mode, InputMode::Help(_));
+        // The matrix and the timeline take the whole width unless a form, the log or a
+        // confirmation is open.
+        let side_pane = match state.shown_mode() {
+            InputMode::Form(_) | InputMode::Log | InputMode::ConfirmDelete(_) => true,
+            _ => matches!(overview, Overview::List(_)),
         };
-
-        let hotkey_hints = [Text::raw("[+]New entry")];
-        let mut hotkey_helper = Paragraph::new(hotkey_hints.iter());
-        let hotkey_chunk_ix = match &state.mode {
-            InputMode::List => 2,
-            InputMode::Input(_) => 3,
+        let main_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(if side_pane {
+                vec![Constraint::Percentage(40), Constraint::Percentage(60)]
+            } else {
+                vec![Constraint::Percentage(100)]
+            })
+            .split(chunks[1]);
+        overview.render(&mut f, main_chunks[0]);
+        if side_pane {
+            match state.shown_mode() {
+                InputMode::Form(form) => {
+                    let cursor = form.render(&mut f, main_chunks[1]);
+                    if !help {
+                        set_cursor = Some(cursor);
+                    }
+                }
+                InputMode::Log => state.messages.render_log(&mut f, main_chunks[1]),
+                _ => detail_pane.render(&mut f, main_chunks[1]),
+            }
+        }
+        if let InputMode::Help(previous) = &state.mode {
+            state
+                .keymap
+                .render_help(state.context(previous), &mut f, chunks[1]);
+        }
+        state.messages.render_status(&mut f, chunks[2]);
+
+        let hints = state.keymap.hints(state.context(&state.mode));
+        let hotkey_hints = match &state.mode {
+            InputMode::ConfirmQuit => [Text::styled(
+                format!("Save changes before quitting? {}", hints),
+                Style::default().fg(Color::Yellow),
+            )],
+            InputMode::ConfirmDelete(entry) => [Text::styled(
+                format!("Delete {}? {}", entry.label(), hints),
+                Style::default().fg(Color::Yellow),
+            )],
+            InputMode::Filter => {
+                set_cursor = Some((
+                    chunks[3].left() + 1 + state.filter.chars().count() as u16,
+                    chunks[3].top(),
+                ));
+                [Text::raw(format!(
+                    "/{}    {}, e.g. /ada or /rust>=3",
+                    state.filter, hints
+                ))]
+            }
+            _ => [Text::raw(hints)],
         };
-        hotkey_helper.render(&mut f, chunks[hotkey_chunk_ix]);
+        let mut hotkey_helper = Paragraph::new(hotkey_hints.iter());
+        hotkey_helper.render(&mut f, chunks[3]);
     })?;
 
     if let Some((x, y)) = set_cursor {
@@ -330,29 +941,3 @@ fn draw(terminal: &mut Terminal<impl Backend>, state: &State, db: &Db) -> Result
 
     Ok(())
 }
-
-fn retrieve_data(state: &State, db: &Db) -> Vec<String> {
-    match state.open_tab {
-        Header::Skills => db
-            .skills
-            .find_skills()
-            .unwrap()
-            .into_iter()
-            .map(|s| format!("{}\n", s.label))
-            .collect(),
-        Header::Projects => db
-            .projects
-            .find_projects()
-            .unwrap()
-            .into_iter()
-            .map(|p| format!("{}\n", p.label))
-            .collect(),
-        Header::Employees => db
-            .employees
-            .find_employees()
-            .unwrap()
-            .into_iter()
-            .map(|e| format!("{} {}\n", e.first_name, e.last_name))
-            .collect(),
-    }
-}
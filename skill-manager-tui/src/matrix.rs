@@ -0,0 +1,225 @@
+use crate::{entries::Entry, Result};
+use skill_manager::{
+    employees::Employee,
+    skills::{usecase::FindSkills, SkillLabel},
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Paragraph, Text, Widget},
+    Frame,
+};
+
+/// The width of the employee names, longer ones are cut.
+const NAME_WIDTH: usize = 20;
+/// The widest column, longer skill labels are cut.
+const MAX_COLUMN_WIDTH: usize = 12;
+
+/// The skill matrix: employees as rows, skills as columns and cells colored by level.
+pub struct Matrix {
+    title: String,
+    skills: Vec<SkillLabel>,
+    rows: Vec<Row>,
+    selected: Option<usize>,
+    column: Option<usize>,
+}
+
+struct Row {
+    name: String,
+    /// The level of each skill, `None` if the employee doesn't know the skill.
+    cells: Vec<Option<Cell>>,
+}
+
+enum Cell {
+    Level(usize),
+    /// A secret skill of an unprivileged view.
+    Masked,
+}
+
+/// The columns of the matrix, all skills by label.
+pub fn skills(db: &UnitOfWork) -> Result<Vec<SkillLabel>> {
+    let mut skills: Vec<SkillLabel> = db
+        .skills
+        .find_skills(&Default::default())?
+        .items
+        .into_iter()
+        .map(|skill| skill.label)
+        .collect();
+    skills.sort_by_key(|skill| skill.0.to_lowercase());
+    Ok(skills)
+}
+
+/// The level of an employee's skill, secret skills count as unknown unless they are shown.
+pub fn level(employee: &Employee, skill: &SkillLabel, include_secret: bool) -> Option<usize> {
+    employee
+        .skills
+        .iter()
+        .find(|assignment| assignment.label == *skill && (include_secret || !assignment.secret))
+        .map(|assignment| assignment.level.0)
+}
+
+/// Sorts employees by their level of a skill, the highest first.
+pub fn sort(entries: &mut [Entry], skill: &SkillLabel, include_secret: bool) {
+    entries.sort_by_key(|entry| match entry {
+        Entry::Employee(employee) => std::cmp::Reverse(level(employee, skill, include_secret)),
+        _ => std::cmp::Reverse(None),
+    });
+}
+
+impl Matrix {
+    pub fn new(
+        entries: &[Entry],
+        skills: Vec<SkillLabel>,
+        selected: Option<usize>,
+        column: Option<usize>,
+        sort: Option<&SkillLabel>,
+        include_secret: bool,
+    ) -> Self {
+        let rows = entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Employee(employee) => Some(Row {
+                    name: entry.label(),
+                    cells: skills
+                        .iter()
+                        .map(|skill| {
+                            employee
+                                .skills
+                                .iter()
+                                .find(|assignment| assignment.label == *skill)
+                                .map(|assignment| {
+                                    if assignment.secret && !include_secret {
+                                        Cell::Masked
+                                    } else {
+                                        Cell::Level(assignment.level.0)
+                                    }
+                                })
+                        })
+                        .collect(),
+                }),
+                _ => None,
+            })
+            .collect();
+        let title = match sort {
+            Some(skill) => format!("Matrix sorted by {}", skill),
+            None => "Matrix".to_string(),
+        };
+        Self {
+            title,
+            skills,
+            rows,
+            selected,
+            column,
+        }
+    }
+
+    /// Renders the matrix scrolled to the selected row and column.
+    pub fn render(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        let widths: Vec<usize> = self
+            .skills
+            .iter()
+            .map(|skill| skill.0.chars().count().clamp(3, MAX_COLUMN_WIDTH) + 1)
+            .collect();
+        let available = (chunk.width as usize).saturating_sub(NAME_WIDTH + 2);
+        let first_column = match self.column {
+            Some(column) => (0..=column)
+                .find(|first| widths[*first..=column].iter().sum::<usize>() <= available)
+                .unwrap_or(column),
+            None => 0,
+        };
+        let mut columns = vec![];
+        let mut width = 0;
+        for (ix, column_width) in widths.iter().enumerate().skip(first_column) {
+            if width + column_width > available {
+                break;
+            }
+            width += column_width;
+            columns.push(ix);
+        }
+        // The title and the column headers take two lines.
+        let height = (chunk.height as usize).saturating_sub(2).max(1);
+        let first_row = match self.selected {
+            Some(selected) if selected >= height => selected + 1 - height,
+            _ => 0,
+        };
+
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+        let mut lines = vec![Text::raw(format!("{:w$}", "", w = NAME_WIDTH + 2))];
+        for &ix in &columns {
+            let style = if Some(ix) == self.column {
+                selected_style
+            } else {
+                Style::default().modifier(Modifier::BOLD)
+            };
+            lines.push(Text::styled(
+                format!(
+                    "{:<w$}",
+                    cut(&self.skills[ix].0, widths[ix] - 1),
+                    w = widths[ix]
+                ),
+                style,
+            ));
+        }
+        lines.push(Text::raw("\n"));
+        for (ix, row) in self.rows.iter().enumerate().skip(first_row).take(height) {
+            let selected = Some(ix) == self.selected;
+            let (symbol, style) = if selected {
+                ("> ", selected_style)
+            } else {
+                ("  ", Style::default())
+            };
+            lines.push(Text::styled(
+                format!(
+                    "{}{:<w$}",
+                    symbol,
+                    cut(&row.name, NAME_WIDTH),
+                    w = NAME_WIDTH
+                ),
+                style,
+            ));
+            for &column in &columns {
+                let cell_width = widths[column] - 1;
+                let (text, mut cell_style) = match &row.cells[column] {
+                    Some(Cell::Level(level)) => (
+                        level.to_string(),
+                        Style::default().fg(Color::Black).bg(level_color(*level)),
+                    ),
+                    Some(Cell::Masked) => (
+                        "*".to_string(),
+                        Style::default().fg(Color::Black).bg(Color::DarkGray),
+                    ),
+                    None => ("·".to_string(), Style::default().fg(Color::DarkGray)),
+                };
+                if selected && Some(column) == self.column {
+                    cell_style = cell_style.modifier(Modifier::BOLD | Modifier::UNDERLINED);
+                }
+                lines.push(Text::styled(
+                    format!("{:^w$}", text, w = cell_width),
+                    cell_style,
+                ));
+                lines.push(Text::raw(" "));
+            }
+            lines.push(Text::raw("\n"));
+        }
+        Paragraph::new(lines.iter())
+            .block(Block::default().title(&self.title))
+            .render(f, chunk);
+    }
+}
+
+/// The color of a level, from cold beginners to hot experts.
+fn level_color(level: usize) -> Color {
+    match level {
+        0 | 1 => Color::Blue,
+        2 => Color::Cyan,
+        3 => Color::Green,
+        4 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
+
+fn cut(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
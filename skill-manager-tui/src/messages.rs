@@ -0,0 +1,94 @@
+use time::OffsetDateTime;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph, Text, Widget},
+    Frame,
+};
+
+/// The outcomes of actions. The latest one is shown in the status bar until the next key is
+/// pressed, all of them in the message log.
+#[derive(Default)]
+pub struct Messages {
+    log: Vec<Message>,
+    /// Whether the latest message is shown in the status bar.
+    shown: bool,
+}
+
+struct Message {
+    time: OffsetDateTime,
+    error: bool,
+    text: String,
+}
+
+impl Messages {
+    pub const SIZE: u16 = 1;
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(false, text.into());
+    }
+
+    /// Logs an error with its causes, keeping the application running.
+    pub fn error(&mut self, error: &anyhow::Error) {
+        self.push(true, format!("{:#}", error));
+    }
+
+    fn push(&mut self, error: bool, text: String) {
+        self.log.push(Message {
+            time: OffsetDateTime::try_now_local().unwrap_or_else(|_| OffsetDateTime::now_utc()),
+            error,
+            text,
+        });
+        self.shown = true;
+    }
+
+    /// Hides the latest message from the status bar.
+    pub fn dismiss(&mut self) {
+        self.shown = false;
+    }
+
+    pub fn render_status(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        let status = match self.log.last() {
+            Some(message) if self.shown => message.text(),
+            _ => Text::raw(""),
+        };
+        Paragraph::new([status].iter()).render(f, chunk);
+    }
+
+    /// Renders the log, the latest message first.
+    pub fn render_log(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        let lines: Vec<Text> = self
+            .log
+            .iter()
+            .rev()
+            .flat_map(|message| {
+                vec![
+                    Text::styled(
+                        format!("{} ", message.time.format("%T")),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    message.text(),
+                    Text::raw("\n"),
+                ]
+            })
+            .collect();
+        Paragraph::new(lines.iter())
+            .block(Block::default().title("Messages").borders(Borders::LEFT))
+            .wrap(true)
+            .render(f, chunk);
+    }
+}
+
+impl Message {
+    fn text(&self) -> Text<'static> {
+        if self.error {
+            Text::styled(
+                format!("Error: {}", self.text),
+                Style::default().fg(Color::Red),
+            )
+        } else {
+            Text::styled(self.text.clone(), Style::default().fg(Color::Green))
+        }
+    }
+}
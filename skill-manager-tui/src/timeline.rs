@@ -0,0 +1,527 @@
+use crate::{entries::Entry, Result};
+use skill_manager::{
+    employees::{Employee, ProjectAssignment},
+    projects::{usecase::FindProjects, ProjectLabel},
+};
+use skill_manager_file::unit_of_work::UnitOfWork;
+use std::fmt::{self, Display};
+use time::{Date, OffsetDateTime};
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Paragraph, Text, Widget},
+    Frame,
+};
+
+/// The width of the employee names and project labels, longer ones are cut.
+const NAME_WIDTH: usize = 20;
+/// The width of a month or quarter.
+const CELL_WIDTH: usize = 4;
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The length of the periods of the timeline.
+#[derive(Copy, Clone)]
+pub enum Zoom {
+    Month,
+    Quarter,
+}
+
+impl Zoom {
+    /// Switches between months and quarters, keeping the first shown period about the same.
+    pub fn toggle(self, offset: usize) -> (Zoom, usize) {
+        match self {
+            Zoom::Month => (Zoom::Quarter, offset / 3),
+            Zoom::Quarter => (Zoom::Month, offset * 3),
+        }
+    }
+
+    /// The number of the period containing a date, counted from the year 0.
+    fn period(self, date: Date) -> i32 {
+        let month = date.year() * 12 + date.month() as i32 - 1;
+        match self {
+            Zoom::Month => month,
+            Zoom::Quarter => month.div_euclid(3),
+        }
+    }
+
+    fn year(self, period: i32) -> i32 {
+        match self {
+            Zoom::Month => period.div_euclid(12),
+            Zoom::Quarter => period.div_euclid(4),
+        }
+    }
+
+    fn label(self, period: i32) -> String {
+        match self {
+            Zoom::Month => MONTHS[period.rem_euclid(12) as usize].to_string(),
+            Zoom::Quarter => format!("Q{}", period.rem_euclid(4) + 1),
+        }
+    }
+
+    fn starts_year(self, period: i32) -> bool {
+        match self {
+            Zoom::Month => period.rem_euclid(12) == 0,
+            Zoom::Quarter => period.rem_euclid(4) == 0,
+        }
+    }
+}
+
+impl Display for Zoom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Zoom::Month => "month",
+                Zoom::Quarter => "quarter",
+            }
+        )
+    }
+}
+
+/// The project assignments of an employee shown in the timeline, all if no project is chosen.
+pub fn assignments<'a>(
+    employee: &'a Employee,
+    project: Option<&'a ProjectLabel>,
+) -> impl Iterator<Item = &'a ProjectAssignment> {
+    employee
+        .projects
+        .iter()
+        .filter(move |assignment| match project {
+            Some(project) => assignment.label == *project,
+            None => true,
+        })
+}
+
+/// The project after `project` in the order of labels, none after the last one.
+pub fn next_project(
+    db: &UnitOfWork,
+    project: Option<&ProjectLabel>,
+) -> Result<Option<ProjectLabel>> {
+    let mut projects: Vec<ProjectLabel> = db
+        .projects
+        .find_projects(&Default::default())?
+        .items
+        .into_iter()
+        .map(|project| project.label)
+        .collect();
+    projects.sort_by_key(|project| project.0.to_lowercase());
+    Ok(match project {
+        None => projects.into_iter().next(),
+        Some(project) => projects
+            .into_iter()
+            .skip_while(|other| other != project)
+            .nth(1),
+    })
+}
+
+/// The number of periods from the first start of an assignment to today or the last end,
+/// whichever is later.
+pub fn len(entries: &[Entry], project: Option<&ProjectLabel>, zoom: Zoom) -> usize {
+    let (first, last) = range(entries, project, zoom, zoom.period(today()));
+    (last - first + 1) as usize
+}
+
+fn range(entries: &[Entry], project: Option<&ProjectLabel>, zoom: Zoom, today: i32) -> (i32, i32) {
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Employee(employee) => Some(employee),
+            _ => None,
+        })
+        .flat_map(|employee| assignments(employee, project))
+        .fold((today, today), |(first, last), assignment| {
+            let (start, end) = span(assignment, zoom, today);
+            (first.min(start), last.max(end))
+        })
+}
+
+/// The first and last period of an assignment, ongoing ones last until today.
+fn span(assignment: &ProjectAssignment, zoom: Zoom, today: i32) -> (i32, i32) {
+    let start = zoom.period(assignment.start_date);
+    let end = assignment
+        .end_date
+        .map_or(today.max(start), |end_date| zoom.period(end_date));
+    (start, end)
+}
+
+fn today() -> Date {
+    OffsetDateTime::now_utc().date()
+}
+
+/// The project assignments of employees as bars across months or quarters.
+pub struct Timeline {
+    title: String,
+    zoom: Zoom,
+    first: i32,
+    last: i32,
+    /// The number of periods the timeline is scrolled back from its last period.
+    offset: usize,
+    today: i32,
+    rows: Vec<Row>,
+    selected: Option<usize>,
+}
+
+struct Row {
+    name: String,
+    /// The labels of the projects with their first and last period.
+    bars: Vec<(String, i32, i32)>,
+}
+
+/// A row for each employee with the bars of their assignments ordered by start.
+fn rows(entries: &[Entry], project: Option<&ProjectLabel>, zoom: Zoom, today: i32) -> Vec<Row> {
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Employee(employee) => {
+                let mut bars: Vec<(String, i32, i32)> = assignments(employee, project)
+                    .map(|assignment| {
+                        let (start, end) = span(assignment, zoom, today);
+                        (assignment.label.0.clone(), start, end)
+                    })
+                    .collect();
+                bars.sort_by_key(|(_, start, _)| *start);
+                Some(Row {
+                    name: entry.label(),
+                    bars,
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+impl Row {
+    /// The number of projects an employee works on in a period.
+    fn load(&self, period: i32) -> usize {
+        self.bars
+            .iter()
+            .filter(|(_, start, end)| (*start..=*end).contains(&period))
+            .count()
+    }
+
+    /// Whether the employee is on the bench in a period: after the start of the first project
+    /// and up to today, but without any project.
+    fn benched(&self, period: i32, today: i32) -> bool {
+        let first = self.bars.iter().map(|(_, start, _)| *start).min();
+        matches!(first, Some(first) if (first..=today).contains(&period)) && self.load(period) == 0
+    }
+}
+
+impl Timeline {
+    pub fn new(
+        entries: &[Entry],
+        selected: Option<usize>,
+        offset: usize,
+        project: Option<&ProjectLabel>,
+        zoom: Zoom,
+    ) -> Self {
+        let today = zoom.period(today());
+        let (first, last) = range(entries, project, zoom, today);
+        let rows = rows(entries, project, zoom, today);
+        let title = match project {
+            Some(project) => format!("Timeline of {} by {}", project, zoom),
+            None => format!("Timeline by {}", zoom),
+        };
+        Self {
+            title,
+            zoom,
+            first,
+            last,
+            offset,
+            today,
+            rows,
+            selected,
+        }
+    }
+
+    /// Renders the timeline scrolled to the selected employee and back from the last period.
+    pub fn render(&self, f: &mut Frame<impl Backend>, chunk: Rect) {
+        let count = (chunk.width as usize).saturating_sub(NAME_WIDTH + 2) / CELL_WIDTH;
+        let last = (self.last - self.offset as i32).max(self.first);
+        let first = (last - count as i32 + 1).max(self.first);
+        let periods: Vec<i32> = (first..=last).collect();
+        // The title and the two header lines take three lines.
+        let height = (chunk.height as usize).saturating_sub(3).max(1);
+        let (start, end) = self.lines_of_selected();
+        let offset = (end + 1).saturating_sub(height).min(start);
+
+        let selected_style = Style::default().fg(Color::Yellow).modifier(Modifier::BOLD);
+        let indent = Text::raw(format!("{:w$}", "", w = NAME_WIDTH + 2));
+        let mut lines = vec![indent.clone()];
+        for (ix, &period) in periods.iter().enumerate() {
+            // The first period is labeled with its year unless the next one starts a year.
+            let first_labeled = ix == 0 && !self.zoom.starts_year(period + 1);
+            let year = if first_labeled || self.zoom.starts_year(period) {
+                self.zoom.year(period).to_string()
+            } else {
+                String::new()
+            };
+            lines.push(Text::styled(
+                format!("{:<w$}", year, w = CELL_WIDTH),
+                Style::default().modifier(Modifier::BOLD),
+            ));
+        }
+        lines.push(Text::raw("\n"));
+        lines.push(indent);
+        for &period in &periods {
+            let style = if period == self.today {
+                selected_style
+            } else {
+                Style::default()
+            };
+            lines.push(Text::styled(
+                format!("{:<w$}", self.zoom.label(period), w = CELL_WIDTH),
+                style,
+            ));
+        }
+        lines.push(Text::raw("\n"));
+
+        let mut body = vec![];
+        for (ix, row) in self.rows.iter().enumerate() {
+            let (symbol, style) = if Some(ix) == self.selected {
+                ("> ", selected_style)
+            } else {
+                ("  ", Style::default())
+            };
+            let mut line = vec![Text::styled(
+                format!(
+                    "{}{:<w$}",
+                    symbol,
+                    cut(&row.name, NAME_WIDTH),
+                    w = NAME_WIDTH
+                ),
+                style,
+            )];
+            for &period in &periods {
+                line.push(match row.load(period) {
+                    0 if row.benched(period, self.today) => {
+                        Text::styled("░".repeat(CELL_WIDTH), Style::default().fg(Color::DarkGray))
+                    }
+                    0 => Text::raw(" ".repeat(CELL_WIDTH)),
+                    1 => Text::styled("█".repeat(CELL_WIDTH), Style::default().fg(Color::Green)),
+                    load => Text::styled(
+                        format!("{:^w$}", load, w = CELL_WIDTH),
+                        Style::default().fg(Color::Black).bg(Color::Red),
+                    ),
+                });
+            }
+            body.push(line);
+            for (label, start, end) in &row.bars {
+                let mut line = vec![Text::raw(format!(
+                    "    {:<w$}",
+                    cut(label, NAME_WIDTH - 2),
+                    w = NAME_WIDTH - 2
+                ))];
+                for &period in &periods {
+                    line.push(if (*start..=*end).contains(&period) {
+                        // Overlapping projects are highlighted.
+                        let color = if row.load(period) > 1 {
+                            Color::Red
+                        } else {
+                            Color::Cyan
+                        };
+                        Text::styled("█".repeat(CELL_WIDTH), Style::default().fg(color))
+                    } else {
+                        Text::raw(" ".repeat(CELL_WIDTH))
+                    });
+                }
+                body.push(line);
+            }
+        }
+        for line in body.into_iter().skip(offset).take(height) {
+            lines.extend(line);
+            lines.push(Text::raw("\n"));
+        }
+        Paragraph::new(lines.iter())
+            .block(Block::default().title(&self.title))
+            .render(f, chunk);
+    }
+
+    /// The first and last line of the selected employee and their projects.
+    fn lines_of_selected(&self) -> (usize, usize) {
+        let selected = match self.selected {
+            Some(selected) => selected,
+            None => return (0, 0),
+        };
+        let start: usize = self.rows[..selected]
+            .iter()
+            .map(|row| 1 + row.bars.len())
+            .sum();
+        (start, start + self.rows[selected].bars.len())
+    }
+}
+
+fn cut(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use skill_manager::{
+        employees::{
+            EmailAddress, FirstName, LastName, ProjectContribution, TelephoneNumber, Title,
+        },
+        projects::ProjectDescription,
+        version::Version,
+    };
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::try_from_ymd(year, month, day).unwrap()
+    }
+
+    fn employee(name: &str, projects: &[(&str, Date, Option<Date>)]) -> Entry {
+        Entry::Employee(Employee {
+            id: "9f6d3c1e-2b8a-4a8e-9c56-3f1e0d7b2a41".parse().unwrap(),
+            first_name: FirstName(name.into()),
+            last_name: LastName("Test".into()),
+            title: Title("".into()),
+            email: EmailAddress("".into()),
+            telephone: TelephoneNumber("".into()),
+            skills: vec![],
+            projects: projects
+                .iter()
+                .map(|&(label, start_date, end_date)| ProjectAssignment {
+                    id: "0b6a7f4e-8d1c-4f2a-9e3b-5c7d9a1b2c3d".parse().unwrap(),
+                    label: ProjectLabel(label.into()),
+                    description: ProjectDescription("".into()),
+                    contribution: ProjectContribution("".into()),
+                    start_date,
+                    end_date,
+                })
+                .collect(),
+            last_update: OffsetDateTime::now_utc(),
+            version: Version::default(),
+        })
+    }
+
+    #[test]
+    fn period_boundaries_test() {
+        let months: Vec<i32> = vec![
+            date(2019, 12, 31),
+            date(2020, 1, 1),
+            date(2020, 3, 31),
+            date(2020, 4, 1),
+        ]
+        .into_iter()
+        .map(|date| Zoom::Month.period(date))
+        .collect();
+        assert_eq!(months[1] - months[0], 1);
+        assert_eq!(months[3] - months[2], 1);
+        let labels: Vec<String> = months.iter().map(|&m| Zoom::Month.label(m)).collect();
+        assert_eq!(labels, vec!["Dec", "Jan", "Mar", "Apr"]);
+        assert_eq!(Zoom::Month.year(months[0]), 2019);
+        assert_eq!(Zoom::Month.year(months[1]), 2020);
+        assert!(Zoom::Month.starts_year(months[1]));
+        assert!(!Zoom::Month.starts_year(months[2]));
+
+        let quarter = |date| Zoom::Quarter.period(date);
+        assert_eq!(quarter(date(2020, 1, 1)), quarter(date(2020, 3, 31)));
+        assert_eq!(quarter(date(2020, 4, 1)), quarter(date(2020, 3, 31)) + 1);
+        assert_eq!(quarter(date(2020, 1, 1)), quarter(date(2019, 12, 31)) + 1);
+        assert_eq!(Zoom::Quarter.label(quarter(date(2019, 12, 31))), "Q4");
+        assert_eq!(Zoom::Quarter.label(quarter(date(2020, 1, 1))), "Q1");
+        assert_eq!(Zoom::Quarter.label(quarter(date(2020, 8, 15))), "Q3");
+        assert_eq!(Zoom::Quarter.year(quarter(date(2019, 12, 31))), 2019);
+        assert!(Zoom::Quarter.starts_year(quarter(date(2020, 2, 1))));
+        assert!(!Zoom::Quarter.starts_year(quarter(date(2020, 4, 1))));
+    }
+
+    #[test]
+    fn toggle_test() {
+        assert!(matches!(Zoom::Month.toggle(7), (Zoom::Quarter, 2)));
+        assert!(matches!(Zoom::Quarter.toggle(2), (Zoom::Month, 6)));
+        assert!(matches!(Zoom::Month.toggle(0), (Zoom::Quarter, 0)));
+    }
+
+    #[test]
+    fn span_test() {
+        let zoom = Zoom::Month;
+        let today = zoom.period(date(2020, 6, 15));
+        let ended = employee("Ada", &[("A", date(2020, 1, 1), Some(date(2020, 2, 29)))]);
+        let ongoing = employee("Ada", &[("A", date(2020, 1, 1), None)]);
+        let future = employee("Ada", &[("A", date(2020, 9, 1), None)]);
+        let span_of = |entry: &Entry| match entry {
+            Entry::Employee(employee) => span(&employee.projects[0], zoom, today),
+            _ => unreachable!(),
+        };
+
+        let january = zoom.period(date(2020, 1, 1));
+        assert_eq!(span_of(&ended), (january, january + 1));
+        assert_eq!(span_of(&ongoing), (january, today));
+        assert_eq!(span_of(&future), (january + 8, january + 8));
+    }
+
+    #[test]
+    fn range_test() {
+        let zoom = Zoom::Quarter;
+        let today = zoom.period(date(2020, 6, 15));
+        let entries = vec![
+            employee("Ada", &[("A", date(2019, 11, 1), Some(date(2020, 1, 31)))]),
+            employee("Grace", &[("B", date(2021, 1, 1), Some(date(2021, 8, 1)))]),
+            employee("Bench", &[]),
+        ];
+
+        assert_eq!(range(&entries, None, zoom, today), (today - 2, today + 5));
+        let a = ProjectLabel("A".into());
+        assert_eq!(range(&entries, Some(&a), zoom, today), (today - 2, today));
+        assert_eq!(range(&entries[2..], None, zoom, today), (today, today));
+        assert_eq!(range(&[], None, zoom, today), (today, today));
+    }
+
+    #[test]
+    fn load_and_bench_test() {
+        let zoom = Zoom::Month;
+        let today = zoom.period(date(2020, 12, 15));
+        let january = zoom.period(date(2020, 1, 1));
+        let entries = vec![
+            employee(
+                "Ada",
+                &[
+                    ("B", date(2020, 6, 1), None),
+                    ("A", date(2020, 1, 1), Some(date(2020, 3, 31))),
+                    ("C", date(2020, 3, 1), Some(date(2020, 3, 31))),
+                ],
+            ),
+            employee("Bench", &[]),
+            Entry::Project(skill_manager::projects::Project {
+                id: "0b6a7f4e-8d1c-4f2a-9e3b-5c7d9a1b2c3d".parse().unwrap(),
+                label: ProjectLabel("A".into()),
+                description: ProjectDescription("".into()),
+                version: Version::default(),
+            }),
+        ];
+
+        let rows = rows(&entries, None, zoom, today);
+
+        assert_eq!(rows.len(), 2);
+        let ada = &rows[0];
+        assert_eq!(ada.name, "Ada Test");
+        let labels: Vec<&str> = ada
+            .bars
+            .iter()
+            .map(|(label, _, _)| label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["A", "C", "B"]);
+        assert_eq!(ada.load(january - 1), 0);
+        assert!(!ada.benched(january - 1, today));
+        assert_eq!(ada.load(january), 1);
+        assert_eq!(ada.load(january + 2), 2);
+        assert_eq!(ada.load(january + 3), 0);
+        assert!(ada.benched(january + 3, today));
+        assert!(ada.benched(january + 4, today));
+        assert_eq!(ada.load(today), 1);
+        assert!(!ada.benched(today, today));
+        assert_eq!(ada.load(today + 1), 0);
+        assert!(!ada.benched(today + 1, today));
+
+        let bench = &rows[1];
+        assert!(bench.bars.is_empty());
+        assert_eq!(bench.load(today), 0);
+        assert!(!bench.benched(today, today));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     projects::{ProjectDescription, ProjectLabel},
     skills::SkillLabel,
+    version::Version,
 };
 use serde::{Deserialize, Serialize};
 use time::{Date, OffsetDateTime};
@@ -19,6 +20,8 @@ pub struct Employee {
     pub skills: Vec<SkillAssignment>,
     pub projects: Vec<ProjectAssignment>,
     pub last_update: OffsetDateTime,
+    #[serde(default)]
+    pub version: Version,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Deserialize, Serialize, Hash)]
@@ -30,6 +33,7 @@ pub struct SkillAssignment {
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Deserialize, Serialize, Hash)]
 pub struct ProjectAssignment {
+    pub id: ProjectAssignmentId,
     pub label: ProjectLabel,
     pub description: ProjectDescription,
     pub contribution: ProjectContribution,
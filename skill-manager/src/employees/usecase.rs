@@ -2,10 +2,12 @@ use super::SkillAssignment;
 use crate::{
     employees::{
         EmailAddress, Employee, EmployeeId, FirstName, LastName, ProjectAssignment,
-        ProjectContribution, SkillLevel, TelephoneNumber, Title,
+        ProjectAssignmentId, ProjectContribution, SkillLevel, TelephoneNumber, Title,
     },
     projects::ProjectId,
-    skills::SkillId,
+    query::{Page, Query},
+    skills::{usecase::SkillNotFoundError, SkillId},
+    version::{Version, VersionConflictError},
 };
 use thiserror::Error;
 use time::Date;
@@ -32,28 +34,80 @@ where
 }
 
 pub trait DeleteEmployeeById {
-    fn delete(&mut self, employee_id: EmployeeId) -> crate::Result<()>;
+    fn delete(
+        &mut self,
+        employee_id: EmployeeId,
+        expected_version: Option<Version>,
+    ) -> crate::Result<()>;
 }
 
 impl<F> DeleteEmployeeById for F
 where
-    F: Fn(EmployeeId) -> crate::Result<()>,
+    F: Fn(EmployeeId, Option<Version>) -> crate::Result<()>,
 {
-    fn delete(&mut self, employee_id: EmployeeId) -> crate::Result<()> {
-        self(employee_id)
+    fn delete(
+        &mut self,
+        employee_id: EmployeeId,
+        expected_version: Option<Version>,
+    ) -> crate::Result<()> {
+        self(employee_id, expected_version)
     }
 }
 
+pub struct UpdateEmployeeRequest {
+    pub employee_id: EmployeeId,
+    pub first_name: FirstName,
+    pub last_name: LastName,
+    pub title: Title,
+    pub email: EmailAddress,
+    pub telephone: TelephoneNumber,
+    pub expected_version: Option<Version>,
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateEmployeeError {
+    #[error(transparent)]
+    EmployeeNotFound(#[from] EmployeeNotFoundError),
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
+}
+
+pub trait UpdateEmployee {
+    fn update(&mut self, request: UpdateEmployeeRequest) -> Result<Employee, UpdateEmployeeError>;
+}
+
+impl<F> UpdateEmployee for F
+where
+    F: FnMut(UpdateEmployeeRequest) -> Result<Employee, UpdateEmployeeError>,
+{
+    fn update(&mut self, request: UpdateEmployeeRequest) -> Result<Employee, UpdateEmployeeError> {
+        self(request)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum EmployeeSortKey {
+    /// Last name, then first name.
+    #[default]
+    Name,
+    LastUpdate,
+    /// The earliest start date of the employee's project assignments, employees without
+    /// project assignments come last in both directions.
+    StartDate,
+}
+
+pub type EmployeeQuery = Query<EmployeeSortKey>;
+
 pub trait FindEmployees {
-    fn find_employees(&self) -> crate::Result<Vec<Employee>>;
+    fn find_employees(&self, query: &EmployeeQuery) -> crate::Result<Page<Employee>>;
 }
 
 impl<F> FindEmployees for F
 where
-    F: Fn() -> crate::Result<Vec<Employee>>,
+    F: Fn(&EmployeeQuery) -> crate::Result<Page<Employee>>,
 {
-    fn find_employees(&self) -> crate::Result<Vec<Employee>> {
-        self()
+    fn find_employees(&self, query: &EmployeeQuery) -> crate::Result<Page<Employee>> {
+        self(query)
     }
 }
 
@@ -76,6 +130,7 @@ pub struct ProjectAssignmentRequest {
     pub contribution: ProjectContribution,
     pub start_date: Date,
     pub end_date: Option<Date>,
+    pub expected_version: Option<Version>,
 }
 
 #[derive(Error, Debug)]
@@ -84,14 +139,16 @@ pub enum AssignProjectToEmployeeError {
     EmployeeNotFound(#[from] EmployeeNotFoundError),
     #[error(transparent)]
     ProjectNotFound(#[from] ProjectNotFoundError),
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
 }
 
-#[error("Employee not found")]
 #[derive(Error, Debug)]
+#[error("Employee not found")]
 pub struct EmployeeNotFoundError;
 
-#[error("Project not found")]
 #[derive(Error, Debug)]
+#[error("Project not found")]
 pub struct ProjectNotFoundError;
 
 pub trait CreateProjectAssignment {
@@ -118,27 +175,39 @@ pub enum DeleteProjectAssignmentError {
     #[error(transparent)]
     EmployeeNotFound(#[from] EmployeeNotFoundError),
     #[error(transparent)]
-    ProjectNotFound(#[from] ProjectNotFoundError),
+    AssignmentNotFound(#[from] ProjectAssignmentNotFoundError),
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
 }
 
+#[derive(Error, Debug)]
+#[error("Project assignment not found")]
+pub struct ProjectAssignmentNotFoundError;
+
 pub trait DeleteProjectAssignment {
     fn delete_project_assignment(
         &mut self,
         employee_id: EmployeeId,
-        assignment_id: String,
+        assignment_id: ProjectAssignmentId,
+        expected_version: Option<Version>,
     ) -> Result<(), DeleteProjectAssignmentError>;
 }
 
 impl<F> DeleteProjectAssignment for F
 where
-    F: FnMut(EmployeeId, String) -> Result<(), DeleteProjectAssignmentError>,
+    F: FnMut(
+        EmployeeId,
+        ProjectAssignmentId,
+        Option<Version>,
+    ) -> Result<(), DeleteProjectAssignmentError>,
 {
     fn delete_project_assignment(
         &mut self,
         employee_id: EmployeeId,
-        assignment_id: String,
+        assignment_id: ProjectAssignmentId,
+        expected_version: Option<Version>,
     ) -> Result<(), DeleteProjectAssignmentError> {
-        self(employee_id, assignment_id)
+        self(employee_id, assignment_id, expected_version)
     }
 }
 
@@ -148,6 +217,8 @@ pub enum AssignSkillToEmployeeError {
     EmployeeNotFound,
     #[error("Skill not found")]
     SkillNotFound,
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
 }
 
 pub struct SetSkillKnowledgeRequest {
@@ -155,6 +226,7 @@ pub struct SetSkillKnowledgeRequest {
     pub skill_id: SkillId,
     pub level: SkillLevel,
     pub secret: bool,
+    pub expected_version: Option<Version>,
 }
 
 pub trait SetSkillKnowledgeOfEmployee {
@@ -176,23 +248,35 @@ where
     }
 }
 
+#[derive(Error, Debug)]
+pub enum DeleteSkillKnowledgeError {
+    #[error(transparent)]
+    EmployeeNotFound(#[from] EmployeeNotFoundError),
+    #[error(transparent)]
+    SkillNotFound(#[from] SkillNotFoundError),
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
+}
+
 pub trait DeleteSkillKnowledgeOfEmployee {
     fn delete_skill_knowledge_of_employee(
         &mut self,
         employee_id: EmployeeId,
         skill_id: SkillId,
-    ) -> crate::Result<()>;
+        expected_version: Option<Version>,
+    ) -> Result<(), DeleteSkillKnowledgeError>;
 }
 
 impl<F> DeleteSkillKnowledgeOfEmployee for F
 where
-    F: FnMut(EmployeeId, SkillId) -> crate::Result<()>,
+    F: FnMut(EmployeeId, SkillId, Option<Version>) -> Result<(), DeleteSkillKnowledgeError>,
 {
     fn delete_skill_knowledge_of_employee(
         &mut self,
         employee_id: EmployeeId,
         skill_id: SkillId,
-    ) -> crate::Result<()> {
-        self(employee_id, skill_id)
+        expected_version: Option<Version>,
+    ) -> Result<(), DeleteSkillKnowledgeError> {
+        self(employee_id, skill_id, expected_version)
     }
 }
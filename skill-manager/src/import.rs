@@ -0,0 +1,830 @@
+//! Maps rows of tabular data, e.g. from a spreadsheet, to use case requests.
+//!
+//! Entities are upserted: skills and projects are matched by label and employees by email
+//! address, ignoring case, so importing the same rows twice leaves the data unchanged. Rows
+//! that can't be imported are rejected with a reason instead of aborting the import.
+//!
+//! Assignments are imported in two steps, first validating the rows against the current data
+//! and then applying the resulting requests.
+
+use crate::{
+    employees::{
+        usecase::{
+            AddEmployee, AddEmployeeRequest, CreateProjectAssignment, EmployeeQuery, FindEmployees,
+            ProjectAssignmentRequest, SetSkillKnowledgeOfEmployee, SetSkillKnowledgeRequest,
+            UpdateEmployee, UpdateEmployeeRequest,
+        },
+        EmailAddress, Employee, FirstName, LastName, ProjectContribution, SkillLevel,
+        TelephoneNumber, Title,
+    },
+    projects::{
+        usecase::{AddProject, FindProjects, ProjectQuery},
+        Project, ProjectDescription, ProjectLabel,
+    },
+    skills::{
+        usecase::{AddSkill, FindSkills, SkillQuery},
+        Skill, SkillLabel,
+    },
+    Result,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use time::Date;
+
+pub const SKILL_COLUMNS: &[&str] = &["label"];
+pub const PROJECT_COLUMNS: &[&str] = &["label", "description"];
+pub const EMPLOYEE_COLUMNS: &[&str] = &["first_name", "last_name", "title", "email", "telephone"];
+pub const SKILL_ASSIGNMENT_COLUMNS: &[&str] = &["email", "skill", "level", "secret"];
+pub const PROJECT_ASSIGNMENT_COLUMNS: &[&str] =
+    &["email", "project", "contribution", "start_date", "end_date"];
+
+/// A row to import, mapping column names to values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// The line the row was read from, used to report rejected rows.
+    pub line: usize,
+    pub columns: HashMap<String, String>,
+}
+
+impl Row {
+    fn required(&self, column: &str) -> std::result::Result<&str, String> {
+        self.optional(column)
+            .ok_or_else(|| format!("Missing value for {}", column))
+    }
+
+    /// Empty values count as missing.
+    fn optional(&self, column: &str) -> Option<&str> {
+        self.columns
+            .get(column)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub rejected: Vec<RejectedRow>,
+}
+
+impl ImportReport {
+    fn reject(&mut self, line: usize, reason: impl ToString) {
+        self.rejected.push(RejectedRow {
+            line,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RejectedRow {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// The requests for the valid rows of an import that still have to be applied.
+pub struct ValidatedRows<T> {
+    pub requests: Vec<ValidatedRow<T>>,
+    /// Contains the rows that were rejected or need no change.
+    pub report: ImportReport,
+}
+
+pub struct ValidatedRow<T> {
+    pub line: usize,
+    pub request: T,
+    /// Whether the request replaces existing data instead of creating new data.
+    pub replaces_existing: bool,
+}
+
+pub fn import_skills(
+    rows: &[Row],
+    skills: &mut (impl FindSkills + AddSkill),
+) -> Result<ImportReport> {
+    let mut labels: HashSet<String> = all_skills(skills)?
+        .into_iter()
+        .map(|skill| skill.label.0.to_lowercase())
+        .collect();
+    let mut report = ImportReport::default();
+    for row in rows {
+        let label = match row.required("label") {
+            Ok(label) => label,
+            Err(reason) => {
+                report.reject(row.line, reason);
+                continue;
+            }
+        };
+        if !labels.insert(label.to_lowercase()) {
+            report.unchanged += 1;
+            continue;
+        }
+        match skills.add(SkillLabel(label.to_string())) {
+            Ok(_) => report.created += 1,
+            Err(e) => report.reject(row.line, e),
+        }
+    }
+    Ok(report)
+}
+
+/// Existing projects are left unchanged, even if their description differs.
+pub fn import_projects(
+    rows: &[Row],
+    projects: &mut (impl FindProjects + AddProject),
+) -> Result<ImportReport> {
+    let mut labels: HashSet<String> = all_projects(projects)?
+        .into_iter()
+        .map(|project| project.label.0.to_lowercase())
+        .collect();
+    let mut report = ImportReport::default();
+    for row in rows {
+        let label = match row.required("label") {
+            Ok(label) => label,
+            Err(reason) => {
+                report.reject(row.line, reason);
+                continue;
+            }
+        };
+        if !labels.insert(label.to_lowercase()) {
+            report.unchanged += 1;
+            continue;
+        }
+        let description = row.optional("description").unwrap_or_default();
+        match projects.add(
+            ProjectLabel(label.to_string()),
+            ProjectDescription(description.to_string()),
+        ) {
+            Ok(_) => report.created += 1,
+            Err(e) => report.reject(row.line, e),
+        }
+    }
+    Ok(report)
+}
+
+/// Employees are matched by email address. Empty optional values keep the existing value of
+/// an employee.
+pub fn import_employees(
+    rows: &[Row],
+    employees: &mut (impl FindEmployees + AddEmployee + UpdateEmployee),
+) -> Result<ImportReport> {
+    let mut by_email = employees_by_email(employees)?;
+    let mut report = ImportReport::default();
+    for row in rows {
+        let (first_name, last_name, email) = match (
+            row.required("first_name"),
+            row.required("last_name"),
+            row.required("email"),
+        ) {
+            (Ok(first_name), Ok(last_name), Ok(email)) => (first_name, last_name, email),
+            (Err(reason), _, _) | (_, Err(reason), _) | (_, _, Err(reason)) => {
+                report.reject(row.line, reason);
+                continue;
+            }
+        };
+        let existing = by_email.get(&email.to_lowercase());
+        let optional = |column, existing: Option<&String>| {
+            row.optional(column)
+                .map(str::to_string)
+                .or_else(|| existing.cloned())
+                .unwrap_or_default()
+        };
+        let title = Title(optional("title", existing.map(|e| &e.title.0)));
+        let telephone = TelephoneNumber(optional("telephone", existing.map(|e| &e.telephone.0)));
+
+        let result = match existing {
+            Some(existing)
+                if existing.first_name.0 == first_name
+                    && existing.last_name.0 == last_name
+                    && existing.email.0 == email
+                    && existing.title == title
+                    && existing.telephone == telephone =>
+            {
+                report.unchanged += 1;
+                continue;
+            }
+            Some(existing) => employees
+                .update(UpdateEmployeeRequest {
+                    employee_id: existing.id.clone(),
+                    first_name: FirstName(first_name.to_string()),
+                    last_name: LastName(last_name.to_string()),
+                    title,
+                    email: EmailAddress(email.to_string()),
+                    telephone,
+                    expected_version: Some(existing.version),
+                })
+                .map(|employee| (employee, false))
+                .map_err(|e| e.to_string()),
+            None => employees
+                .add(AddEmployeeRequest {
+                    first_name: FirstName(first_name.to_string()),
+                    last_name: LastName(last_name.to_string()),
+                    title,
+                    email: EmailAddress(email.to_string()),
+                    telephone,
+                })
+                .map(|employee| (employee, true))
+                .map_err(|e| e.to_string()),
+        };
+        match result {
+            Ok((employee, created)) => {
+                if created {
+                    report.created += 1;
+                } else {
+                    report.updated += 1;
+                }
+                by_email.insert(employee.email.0.to_lowercase(), employee);
+            }
+            Err(reason) => report.reject(row.line, reason),
+        }
+    }
+    Ok(report)
+}
+
+/// Skill assignments reference the employee by email address and the skill by label. An
+/// existing assignment of the same skill is replaced.
+pub fn validate_skill_assignments(
+    rows: &[Row],
+    employees: &impl FindEmployees,
+    skills: &impl FindSkills,
+) -> Result<ValidatedRows<SetSkillKnowledgeRequest>> {
+    let by_email = employees_by_email(employees)?;
+    let skills_by_label: HashMap<String, Skill> = all_skills(skills)?
+        .into_iter()
+        .map(|skill| (skill.label.0.to_lowercase(), skill))
+        .collect();
+    let mut validated = ValidatedRows {
+        requests: vec![],
+        report: ImportReport::default(),
+    };
+    for row in rows {
+        let result = (|| {
+            let employee = find_employee(&by_email, row.required("email")?)?;
+            let label = row.required("skill")?;
+            let skill = skills_by_label
+                .get(&label.to_lowercase())
+                .ok_or_else(|| format!("Unknown skill {}", label))?;
+            let level = row.required("level")?;
+            let level: usize = level
+                .parse()
+                .map_err(|_| format!("Invalid level {}", level))?;
+            let secret = match row.optional("secret") {
+                None => false,
+                Some(secret) => parse_bool(secret)?,
+            };
+            let existing = employee
+                .skills
+                .iter()
+                .find(|assignment| assignment.label == skill.label);
+            if let Some(existing) = existing {
+                if existing.level.0 == level && existing.secret == secret {
+                    return Ok(None);
+                }
+            }
+            Ok(Some(ValidatedRow {
+                line: row.line,
+                request: SetSkillKnowledgeRequest {
+                    employee_id: employee.id.clone(),
+                    skill_id: skill.id.clone(),
+                    level: SkillLevel(level),
+                    secret,
+                    expected_version: None,
+                },
+                replaces_existing: existing.is_some(),
+            }))
+        })();
+        validated.add(row.line, result);
+    }
+    Ok(validated)
+}
+
+pub fn apply_skill_assignments(
+    validated: ValidatedRows<SetSkillKnowledgeRequest>,
+    employees: &mut impl SetSkillKnowledgeOfEmployee,
+) -> ImportReport {
+    validated.apply(|request| {
+        employees
+            .set_skill_knowledge_of_employee(request)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Project assignments reference the employee by email address and the project by label. An
+/// assignment to the same project with the same start date counts as existing and is left
+/// unchanged.
+pub fn validate_project_assignments(
+    rows: &[Row],
+    employees: &impl FindEmployees,
+    projects: &impl FindProjects,
+) -> Result<ValidatedRows<ProjectAssignmentRequest>> {
+    let by_email = employees_by_email(employees)?;
+    let projects_by_label: HashMap<String, Project> = all_projects(projects)?
+        .into_iter()
+        .map(|project| (project.label.0.to_lowercase(), project))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut validated = ValidatedRows {
+        requests: vec![],
+        report: ImportReport::default(),
+    };
+    for row in rows {
+        let result = (|| {
+            let employee = find_employee(&by_email, row.required("email")?)?;
+            let label = row.required("project")?;
+            let project = projects_by_label
+                .get(&label.to_lowercase())
+                .ok_or_else(|| format!("Unknown project {}", label))?;
+            let start_date = parse_date(row.required("start_date")?)?;
+            let end_date = row.optional("end_date").map(parse_date).transpose()?;
+            let contribution = row.optional("contribution").unwrap_or_default();
+
+            let exists = employee.projects.iter().any(|assignment| {
+                assignment.label == project.label && assignment.start_date == start_date
+            });
+            let key = (employee.id.clone(), project.id.clone(), start_date);
+            if exists || !seen.insert(key) {
+                return Ok(None);
+            }
+            Ok(Some(ValidatedRow {
+                line: row.line,
+                request: ProjectAssignmentRequest {
+                    employee_id: employee.id.clone(),
+                    project_id: project.id.clone(),
+                    contribution: ProjectContribution(contribution.to_string()),
+                    start_date,
+                    end_date,
+                    expected_version: None,
+                },
+                replaces_existing: false,
+            }))
+        })();
+        validated.add(row.line, result);
+    }
+    Ok(validated)
+}
+
+pub fn apply_project_assignments(
+    validated: ValidatedRows<ProjectAssignmentRequest>,
+    employees: &mut impl CreateProjectAssignment,
+) -> ImportReport {
+    validated.apply(|request| {
+        employees
+            .create_project_assignment(request)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+}
+
+impl<T> ValidatedRows<T> {
+    /// Adds the outcome of validating a row, `None` means that the row needs no change.
+    fn add(&mut self, line: usize, result: std::result::Result<Option<ValidatedRow<T>>, String>) {
+        match result {
+            Ok(Some(validated)) => self.requests.push(validated),
+            Ok(None) => self.report.unchanged += 1,
+            Err(reason) => self.report.reject(line, reason),
+        }
+    }
+
+    fn apply(self, mut apply: impl FnMut(T) -> std::result::Result<(), String>) -> ImportReport {
+        let mut report = self.report;
+        for validated in self.requests {
+            match apply(validated.request) {
+                Ok(()) if validated.replaces_existing => report.updated += 1,
+                Ok(()) => report.created += 1,
+                Err(reason) => report.reject(validated.line, reason),
+            }
+        }
+        report.rejected.sort_by_key(|rejected| rejected.line);
+        report
+    }
+}
+
+fn all_skills(skills: &impl FindSkills) -> Result<Vec<Skill>> {
+    Ok(skills.find_skills(&SkillQuery::default())?.items)
+}
+
+fn all_projects(projects: &impl FindProjects) -> Result<Vec<Project>> {
+    Ok(projects.find_projects(&ProjectQuery::default())?.items)
+}
+
+fn employees_by_email(employees: &impl FindEmployees) -> Result<HashMap<String, Employee>> {
+    Ok(employees
+        .find_employees(&EmployeeQuery::default())?
+        .items
+        .into_iter()
+        .filter(|employee| !employee.email.0.is_empty())
+        .map(|employee| (employee.email.0.to_lowercase(), employee))
+        .collect())
+}
+
+fn find_employee<'a>(
+    by_email: &'a HashMap<String, Employee>,
+    email: &str,
+) -> std::result::Result<&'a Employee, String> {
+    by_email
+        .get(&email.to_lowercase())
+        .ok_or_else(|| format!("Unknown employee {}", email))
+}
+
+fn parse_date(s: &str) -> std::result::Result<Date, String> {
+    time::parse(s, "%F").map_err(|_| format!("Invalid date {}, expected YYYY-MM-DD", s))
+}
+
+fn parse_bool(s: &str) -> std::result::Result<bool, String> {
+    match s.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("Invalid boolean {}, expected true or false", s)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        employees::{
+            usecase::UpdateEmployeeError, EmployeeId, ProjectAssignment, ProjectAssignmentId,
+            SkillAssignment,
+        },
+        projects::ProjectId,
+        query::Page,
+        skills::SkillId,
+        version::Version,
+    };
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    /// Just enough of a store to run imports against.
+    #[derive(Default)]
+    struct Db {
+        skills: Vec<Skill>,
+        projects: Vec<Project>,
+        employees: Vec<Employee>,
+    }
+
+    impl FindSkills for Db {
+        fn find_skills(&self, _: &SkillQuery) -> Result<Page<Skill>> {
+            Ok(Page::paginate(self.skills.clone(), Default::default()))
+        }
+    }
+
+    impl AddSkill for Db {
+        fn add(&mut self, label: SkillLabel) -> Result<Skill> {
+            let skill = Skill {
+                id: SkillId(Uuid::new_v4()),
+                label,
+                version: Version::default(),
+            };
+            self.skills.push(skill.clone());
+            Ok(skill)
+        }
+    }
+
+    impl FindProjects for Db {
+        fn find_projects(&self, _: &ProjectQuery) -> Result<Page<Project>> {
+            Ok(Page::paginate(self.projects.clone(), Default::default()))
+        }
+    }
+
+    impl AddProject for Db {
+        fn add(&mut self, label: ProjectLabel, description: ProjectDescription) -> Result<Project> {
+            let project = Project {
+                id: ProjectId(Uuid::new_v4()),
+                label,
+                description,
+                version: Version::default(),
+            };
+            self.projects.push(project.clone());
+            Ok(project)
+        }
+    }
+
+    impl FindEmployees for Db {
+        fn find_employees(&self, _: &EmployeeQuery) -> Result<Page<Employee>> {
+            Ok(Page::paginate(self.employees.clone(), Default::default()))
+        }
+    }
+
+    impl AddEmployee for Db {
+        fn add(&mut self, request: AddEmployeeRequest) -> Result<Employee> {
+            let employee = Employee {
+                id: EmployeeId(Uuid::new_v4()),
+                first_name: request.first_name,
+                last_name: request.last_name,
+                title: request.title,
+                email: request.email,
+                telephone: request.telephone,
+                skills: vec![],
+                projects: vec![],
+                last_update: OffsetDateTime::now_utc(),
+                version: Version::default(),
+            };
+            self.employees.push(employee.clone());
+            Ok(employee)
+        }
+    }
+
+    impl UpdateEmployee for Db {
+        fn update(
+            &mut self,
+            request: UpdateEmployeeRequest,
+        ) -> std::result::Result<Employee, UpdateEmployeeError> {
+            let employee = self
+                .employees
+                .iter_mut()
+                .find(|employee| employee.id == request.employee_id)
+                .ok_or(crate::employees::usecase::EmployeeNotFoundError)?;
+            employee.version.check(request.expected_version)?;
+            employee.first_name = request.first_name;
+            employee.last_name = request.last_name;
+            employee.title = request.title;
+            employee.email = request.email;
+            employee.telephone = request.telephone;
+            employee.version = employee.version.next();
+            Ok(employee.clone())
+        }
+    }
+
+    fn row(line: usize, columns: &[(&str, &str)]) -> Row {
+        Row {
+            line,
+            columns: columns
+                .iter()
+                .map(|(column, value)| (column.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    fn rejected(report: &ImportReport) -> Vec<(usize, &str)> {
+        report
+            .rejected
+            .iter()
+            .map(|rejected| (rejected.line, rejected.reason.as_str()))
+            .collect()
+    }
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::try_from_ymd(year, month, day).unwrap()
+    }
+
+    /// A store with the skill Rust, the project Skill Manager and Ada, who knows Rust and
+    /// worked on Skill Manager.
+    fn db() -> Db {
+        let mut db = Db::default();
+        AddSkill::add(&mut db, SkillLabel("Rust".into())).unwrap();
+        AddProject::add(
+            &mut db,
+            ProjectLabel("Skill Manager".into()),
+            ProjectDescription("Internal tool".into()),
+        )
+        .unwrap();
+        let mut ada = AddEmployee::add(
+            &mut db,
+            AddEmployeeRequest {
+                first_name: FirstName("Ada".into()),
+                last_name: LastName("Lovelace".into()),
+                title: Title("Consultant".into()),
+                email: EmailAddress("ada@example.com".into()),
+                telephone: TelephoneNumber("123".into()),
+            },
+        )
+        .unwrap();
+        ada.skills.push(SkillAssignment {
+            label: SkillLabel("Rust".into()),
+            level: SkillLevel(3),
+            secret: false,
+        });
+        ada.projects.push(ProjectAssignment {
+            id: ProjectAssignmentId(Uuid::new_v4()),
+            label: ProjectLabel("Skill Manager".into()),
+            description: ProjectDescription("Internal tool".into()),
+            contribution: ProjectContribution("".into()),
+            start_date: date(2020, 1, 1),
+            end_date: None,
+        });
+        db.employees = vec![ada];
+        db
+    }
+
+    #[test]
+    fn import_skills_test() -> Result<()> {
+        let mut db = db();
+        let rows = vec![
+            row(2, &[("label", "rust")]),
+            row(3, &[("label", "Go")]),
+            row(4, &[("label", " go ")]),
+            row(5, &[("label", "")]),
+        ];
+
+        let report = import_skills(&rows, &mut db)?;
+
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (1, 0, 2)
+        );
+        assert_eq!(rejected(&report), vec![(5, "Missing value for label")]);
+        let labels: Vec<&str> = db
+            .skills
+            .iter()
+            .map(|skill| skill.label.0.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Rust", "Go"]);
+        Ok(())
+    }
+
+    #[test]
+    fn import_projects_test() -> Result<()> {
+        let mut db = db();
+        let rows = vec![
+            row(2, &[("label", "SKILL MANAGER"), ("description", "Changed")]),
+            row(3, &[("label", "Website")]),
+            row(4, &[("description", "No label")]),
+        ];
+
+        let report = import_projects(&rows, &mut db)?;
+
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (1, 0, 1)
+        );
+        assert_eq!(rejected(&report), vec![(4, "Missing value for label")]);
+        assert_eq!(db.projects[0].description.0, "Internal tool");
+        assert_eq!(db.projects[1].label.0, "Website");
+        assert_eq!(db.projects[1].description.0, "");
+        Ok(())
+    }
+
+    #[test]
+    fn import_employees_test() -> Result<()> {
+        let mut db = db();
+        let ada = |title: &'static str, telephone: &'static str| {
+            vec![
+                ("first_name", "Ada"),
+                ("last_name", "Lovelace"),
+                ("email", "ada@example.com"),
+                ("title", title),
+                ("telephone", telephone),
+            ]
+        };
+        let rows = vec![
+            row(2, &ada("Consultant", "")),
+            row(3, &ada("Senior Consultant", "")),
+            row(
+                4,
+                &[
+                    ("first_name", "Grace"),
+                    ("last_name", "Hopper"),
+                    ("email", "grace@example.com"),
+                ],
+            ),
+            row(5, &[("first_name", "Alan"), ("last_name", "Turing")]),
+        ];
+
+        let report = import_employees(&rows, &mut db)?;
+
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (1, 1, 1)
+        );
+        assert_eq!(rejected(&report), vec![(5, "Missing value for email")]);
+        assert_eq!(db.employees.len(), 2);
+        assert_eq!(db.employees[0].title.0, "Senior Consultant");
+        assert_eq!(db.employees[0].telephone.0, "123");
+        assert_eq!(db.employees[0].version, Version(1));
+        assert_eq!(db.employees[1].first_name.0, "Grace");
+
+        let report = import_employees(&rows[1..3], &mut db)?;
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (0, 0, 2)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn skill_assignments_test() -> Result<()> {
+        let mut db = db();
+        AddSkill::add(&mut db, SkillLabel("Go".into()))?;
+        let assignment = |email: &str, skill: &str, level: &str, secret: &str| {
+            vec![
+                ("email", email.to_string()),
+                ("skill", skill.to_string()),
+                ("level", level.to_string()),
+                ("secret", secret.to_string()),
+            ]
+        };
+        let rows: Vec<Row> = vec![
+            assignment("ADA@example.com", "rust", "3", "no"),
+            assignment("ada@example.com", "Rust", "4", ""),
+            assignment("ada@example.com", "go", "2", "yes"),
+            assignment("bob@example.com", "Rust", "1", ""),
+            assignment("ada@example.com", "Cobol", "1", ""),
+            assignment("ada@example.com", "Rust", "high", ""),
+            assignment("ada@example.com", "Rust", "1", "maybe"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(ix, columns)| Row {
+            line: ix + 2,
+            columns: columns
+                .into_iter()
+                .map(|(column, value)| (column.to_string(), value))
+                .collect(),
+        })
+        .collect();
+
+        let validated = validate_skill_assignments(&rows, &db, &db)?;
+
+        assert_eq!(validated.report.unchanged, 1);
+        assert_eq!(
+            rejected(&validated.report),
+            vec![
+                (5, "Unknown employee bob@example.com"),
+                (6, "Unknown skill Cobol"),
+                (7, "Invalid level high"),
+                (8, "Invalid boolean maybe, expected true or false"),
+            ]
+        );
+        let requests: Vec<(usize, usize, bool, bool)> = validated
+            .requests
+            .iter()
+            .map(|row| {
+                (
+                    row.line,
+                    row.request.level.0,
+                    row.request.secret,
+                    row.replaces_existing,
+                )
+            })
+            .collect();
+        assert_eq!(requests, vec![(3, 4, false, true), (4, 2, true, false)]);
+
+        let go = db.skills[1].id.clone();
+        let report =
+            apply_skill_assignments(validated, &mut |request: SetSkillKnowledgeRequest| {
+                if request.skill_id == go {
+                    Err(crate::employees::usecase::AssignSkillToEmployeeError::SkillNotFound)
+                } else {
+                    Ok(SkillAssignment {
+                        label: SkillLabel("Rust".into()),
+                        level: request.level,
+                        secret: request.secret,
+                    })
+                }
+            });
+        assert_eq!(
+            (report.created, report.updated, report.unchanged),
+            (0, 1, 1)
+        );
+        assert_eq!(
+            rejected(&report)[..2],
+            [
+                (4, "Skill not found"),
+                (5, "Unknown employee bob@example.com")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn project_assignments_test() -> Result<()> {
+        let db = db();
+        let assignment =
+            |project: &'static str, start_date: &'static str, end_date: &'static str| {
+                vec![
+                    ("email", "ada@example.com"),
+                    ("project", project),
+                    ("contribution", "Development"),
+                    ("start_date", start_date),
+                    ("end_date", end_date),
+                ]
+            };
+        let rows = vec![
+            row(2, &assignment("skill manager", "2020-01-01", "")),
+            row(3, &assignment("Skill Manager", "2021-01-01", "2021-06-30")),
+            row(4, &assignment("Skill Manager", "2021-01-01", "")),
+            row(5, &assignment("Skill Manager", "01.01.2022", "")),
+            row(6, &assignment("Skill Manager", "2022-01-01", "soon")),
+            row(7, &assignment("Website", "2022-01-01", "")),
+        ];
+
+        let validated = validate_project_assignments(&rows, &db, &db)?;
+
+        assert_eq!(validated.report.unchanged, 2);
+        assert_eq!(
+            rejected(&validated.report),
+            vec![
+                (5, "Invalid date 01.01.2022, expected YYYY-MM-DD"),
+                (6, "Invalid date soon, expected YYYY-MM-DD"),
+                (7, "Unknown project Website"),
+            ]
+        );
+        assert_eq!(validated.requests.len(), 1);
+        let request = &validated.requests[0];
+        assert_eq!(request.line, 3);
+        assert_eq!(request.request.start_date, date(2021, 1, 1));
+        assert_eq!(request.request.end_date, Some(date(2021, 6, 30)));
+        assert!(!request.replaces_existing);
+        Ok(())
+    }
+}
@@ -5,13 +5,24 @@ use thiserror::Error;
 #[macro_use]
 mod wrapper;
 pub mod employees;
+pub mod import;
 pub mod projects;
+pub mod query;
 pub mod skills;
+pub mod version;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Error: {0}")]
     Other(String),
+    #[error(transparent)]
+    VersionConflict(#[from] version::VersionConflictError),
+    #[error(transparent)]
+    SkillNotFound(#[from] skills::usecase::SkillNotFoundError),
+    #[error(transparent)]
+    ProjectNotFound(#[from] employees::usecase::ProjectNotFoundError),
+    #[error(transparent)]
+    EmployeeNotFound(#[from] employees::usecase::EmployeeNotFoundError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
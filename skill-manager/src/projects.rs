@@ -1,3 +1,4 @@
+use crate::version::Version;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -8,6 +9,8 @@ pub struct Project {
     pub id: ProjectId,
     pub label: ProjectLabel,
     pub description: ProjectDescription,
+    #[serde(default)]
+    pub version: Version,
 }
 
 gen_wrapper!(
@@ -1,7 +1,11 @@
 use crate::{
+    employees::usecase::ProjectNotFoundError,
     projects::{Project, ProjectDescription, ProjectId, ProjectLabel},
+    query::{Page, Query},
+    version::{Version, VersionConflictError},
     Result,
 };
+use thiserror::Error;
 
 pub trait AddProject {
     fn add(
@@ -25,28 +29,73 @@ where
 }
 
 pub trait DeleteProject {
-    fn delete(&mut self, project_id: ProjectId) -> Result<()>;
+    fn delete(&mut self, project_id: ProjectId, expected_version: Option<Version>) -> Result<()>;
 }
 
 impl<F> DeleteProject for F
 where
-    F: Fn(ProjectId) -> Result<()>,
+    F: Fn(ProjectId, Option<Version>) -> Result<()>,
 {
-    fn delete(&mut self, project_id: ProjectId) -> Result<()> {
-        self(project_id)
+    fn delete(&mut self, project_id: ProjectId, expected_version: Option<Version>) -> Result<()> {
+        self(project_id, expected_version)
     }
 }
 
+pub struct UpdateProjectRequest {
+    pub project_id: ProjectId,
+    pub label: ProjectLabel,
+    pub description: ProjectDescription,
+    pub expected_version: Option<Version>,
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateProjectError {
+    #[error(transparent)]
+    ProjectNotFound(#[from] ProjectNotFoundError),
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
+}
+
+/// Updates only the project itself. Project assignments copy the label and description of
+/// their project, so the caller has to update them in the employee store as well, e.g. with
+/// the in-memory `EmployeeDb::update_project`.
+pub trait UpdateProject {
+    fn update(
+        &mut self,
+        request: UpdateProjectRequest,
+    ) -> std::result::Result<Project, UpdateProjectError>;
+}
+
+impl<F> UpdateProject for F
+where
+    F: FnMut(UpdateProjectRequest) -> std::result::Result<Project, UpdateProjectError>,
+{
+    fn update(
+        &mut self,
+        request: UpdateProjectRequest,
+    ) -> std::result::Result<Project, UpdateProjectError> {
+        self(request)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ProjectSortKey {
+    #[default]
+    Label,
+}
+
+pub type ProjectQuery = Query<ProjectSortKey>;
+
 pub trait FindProjects {
-    fn find_projects(&self) -> Result<Vec<Project>>;
+    fn find_projects(&self, query: &ProjectQuery) -> Result<Page<Project>>;
 }
 
 impl<F> FindProjects for F
 where
-    F: Fn() -> Result<Vec<Project>>,
+    F: Fn(&ProjectQuery) -> Result<Page<Project>>,
 {
-    fn find_projects(&self) -> Result<Vec<Project>> {
-        self()
+    fn find_projects(&self, query: &ProjectQuery) -> Result<Page<Project>> {
+        self(query)
     }
 }
 
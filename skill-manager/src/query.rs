@@ -0,0 +1,95 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// Parameters of a find use case: which entries to include, in which order and which page
+/// of them to return.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Query<S> {
+    /// Only entries containing this text, ignoring case, are included.
+    pub filter: Option<String>,
+    pub sort: S,
+    pub direction: SortDirection,
+    pub page: PageRequest,
+}
+
+impl<S> Query<S> {
+    /// Whether any of the entry's fields contain the filter text.
+    pub fn matches(&self, fields: &[&str]) -> bool {
+        match &self.filter {
+            Some(filter) => {
+                let filter = filter.to_lowercase();
+                fields
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&filter))
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SortDirection {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    }
+
+    /// Orders optional values in this direction, with missing values last in both directions.
+    pub fn apply_missing_last<T: Ord>(self, a: &Option<T>, b: &Option<T>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => self.apply(a.cmp(b)),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
+}
+
+/// The default page contains all entries.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// One page of the entries matching a query.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub offset: usize,
+    /// The number of matching entries on all pages.
+    pub total: usize,
+}
+
+impl<T> Page<T> {
+    /// Takes the requested page out of the sorted entries.
+    pub fn paginate(sorted: Vec<T>, request: PageRequest) -> Self {
+        let total = sorted.len();
+        let limit = request.limit.unwrap_or(usize::MAX);
+        Page {
+            items: sorted
+                .into_iter()
+                .skip(request.offset)
+                .take(limit)
+                .collect(),
+            offset: request.offset,
+            total,
+        }
+    }
+
+    /// The offset of the next page, if there is one.
+    pub fn next_offset(&self) -> Option<usize> {
+        let next_offset = self.offset + self.items.len();
+        if !self.items.is_empty() && next_offset < self.total {
+            Some(next_offset)
+        } else {
+            None
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::version::Version;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -8,6 +9,8 @@ pub mod usecase;
 pub struct Skill {
     pub id: SkillId,
     pub label: SkillLabel,
+    #[serde(default)]
+    pub version: Version,
 }
 
 gen_wrapper!(SkillId: Uuid, SkillLabel: String);
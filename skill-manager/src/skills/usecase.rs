@@ -1,7 +1,10 @@
 use crate::{
+    query::{Page, Query},
     skills::{Skill, SkillId, SkillLabel},
+    version::{Version, VersionConflictError},
     Result,
 };
+use thiserror::Error;
 
 pub trait AddSkill {
     fn add(&mut self, skill_label: SkillLabel) -> Result<Skill>;
@@ -16,16 +19,64 @@ where
     }
 }
 
+pub struct UpdateSkillRequest {
+    pub skill_id: SkillId,
+    pub label: SkillLabel,
+    pub expected_version: Option<Version>,
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateSkillError {
+    #[error(transparent)]
+    SkillNotFound(#[from] SkillNotFoundError),
+    #[error(transparent)]
+    VersionConflict(#[from] VersionConflictError),
+}
+
+#[derive(Error, Debug)]
+#[error("Skill not found")]
+pub struct SkillNotFoundError;
+
+/// Updates only the skill itself. Skill assignments refer to skills by label, so the caller
+/// has to rename them in the employee store as well, e.g. with the in-memory
+/// `EmployeeDb::rename_skill`.
+pub trait UpdateSkill {
+    fn update(
+        &mut self,
+        request: UpdateSkillRequest,
+    ) -> std::result::Result<Skill, UpdateSkillError>;
+}
+
+impl<F> UpdateSkill for F
+where
+    F: FnMut(UpdateSkillRequest) -> std::result::Result<Skill, UpdateSkillError>,
+{
+    fn update(
+        &mut self,
+        request: UpdateSkillRequest,
+    ) -> std::result::Result<Skill, UpdateSkillError> {
+        self(request)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SkillSortKey {
+    #[default]
+    Label,
+}
+
+pub type SkillQuery = Query<SkillSortKey>;
+
 pub trait FindSkills {
-    fn find_skills(&self) -> Result<Vec<Skill>>;
+    fn find_skills(&self, query: &SkillQuery) -> Result<Page<Skill>>;
 }
 
 impl<F> FindSkills for F
 where
-    F: Fn() -> Result<Vec<Skill>>,
+    F: Fn(&SkillQuery) -> Result<Page<Skill>>,
 {
-    fn find_skills(&self) -> Result<Vec<Skill>> {
-        self()
+    fn find_skills(&self, query: &SkillQuery) -> Result<Page<Skill>> {
+        self(query)
     }
 }
 
@@ -43,14 +94,14 @@ where
 }
 
 pub trait DeleteSkillById {
-    fn delete(&mut self, skill_id: SkillId) -> Result<()>;
+    fn delete(&mut self, skill_id: SkillId, expected_version: Option<Version>) -> Result<()>;
 }
 
 impl<F> DeleteSkillById for F
 where
-    F: FnMut(SkillId) -> Result<()>,
+    F: FnMut(SkillId, Option<Version>) -> Result<()>,
 {
-    fn delete(&mut self, skill_id: SkillId) -> Result<()> {
-        self(skill_id)
+    fn delete(&mut self, skill_id: SkillId, expected_version: Option<Version>) -> Result<()> {
+        self(skill_id, expected_version)
     }
 }
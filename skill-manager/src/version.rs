@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+gen_wrapper!(
+    Version: u64 [Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Deserialize, Serialize, Hash]
+);
+
+impl Version {
+    pub fn next(self) -> Self {
+        Version(self.0 + 1)
+    }
+
+    /// Succeeds if no version is expected or the expected version is the current one.
+    pub fn check(self, expected: Option<Version>) -> Result<(), VersionConflictError> {
+        match expected {
+            Some(expected) if expected != self => Err(VersionConflictError {
+                expected,
+                actual: self,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Version conflict: expected version {expected}, but found version {actual}")]
+pub struct VersionConflictError {
+    pub expected: Version,
+    pub actual: Version,
+}